@@ -0,0 +1,60 @@
+// Run with `cargo bench`, reports end up in target/criterion.
+// Every generate variant is measured in the same group per board size,
+// so they can be compared head to head.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use gameoflife::gol::Engine;
+
+const SEED: u64 = 42;
+const DENSITY: f64 = 0.3;
+
+fn seeded<const H: usize, const W: usize>() -> Engine<H, W> {
+    let mut engine = Engine::<H, W>::new();
+    engine.randomize_seeded(SEED, DENSITY);
+    engine
+}
+
+fn bench_generate<const H: usize, const W: usize>(c: &mut Criterion) {
+    let mut group = c.benchmark_group("generate");
+    let size = format!("{}x{}", H, W);
+
+    // The board keeps evolving between iterations, so it is reseeded for every variant
+    let mut engine = seeded::<H, W>();
+    group.bench_function(BenchmarkId::new("generate", &size), |b| {
+        b.iter(|| engine.generate())
+    });
+
+    let mut engine = seeded::<H, W>();
+    group.bench_function(BenchmarkId::new("generate_sparse", &size), |b| {
+        b.iter(|| engine.generate_sparse())
+    });
+
+    group.finish();
+}
+
+fn bench_randomize<const H: usize, const W: usize>(c: &mut Criterion) {
+    let mut engine = Engine::<H, W>::new();
+    c.bench_function(&format!("randomize {}x{}", H, W), |b| {
+        b.iter(|| {
+            engine.clear();
+            engine.randomize_seeded(SEED, DENSITY);
+        })
+    });
+}
+
+fn bench_render_ascii<const H: usize, const W: usize>(c: &mut Criterion) {
+    let engine = seeded::<H, W>();
+    c.bench_function(&format!("render_ascii {}x{}", H, W), |b| {
+        b.iter(|| engine.render_ascii())
+    });
+}
+
+criterion_group!(
+    benches,
+    bench_generate::<100, 100>,
+    bench_generate::<500, 500>,
+    bench_generate::<1000, 1000>,
+    bench_randomize::<100, 100>,
+    bench_render_ascii::<100, 100>,
+);
+criterion_main!(benches);
@@ -0,0 +1,182 @@
+// Hand rolled parser for the command line arguments
+//
+// Board dimensions are const generics, so --size picks one of a few
+// precompiled sizes instead of taking arbitrary numbers.
+
+use std::fmt;
+
+use gameoflife::gol::patterns::pattern_by_name;
+
+pub const USAGE: &str = "\
+usage: gameoflife [options]
+
+  --size <small|medium|large>  50x50, 100x100 (default) or 200 rows by 400 columns
+  --generations <n>            stop after n generations, headless runs default to 1000
+  --delay <ms>                 delay between frames in milliseconds (default 20)
+  --pattern <name>             start from a catalog pattern instead of a random board
+  --seed <n>                   seed of the random board
+  --headless                   run without a window and print the final state
+  --help                       print this message";
+
+pub const GENERATIONS: usize = 1000;
+pub const DELAY: usize = 20;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum Size {
+    // 50 x 50
+    Small,
+    // 100 x 100
+    #[default]
+    Medium,
+    // 200 rows by 400 columns
+    Large,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Options {
+    pub size: Size,
+    pub generations: Option<usize>,
+    pub delay: usize,
+    pub pattern: Option<String>,
+    pub seed: Option<u64>,
+    pub headless: bool,
+    pub help: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            size: Size::default(),
+            generations: None,
+            delay: DELAY,
+            pattern: None,
+            seed: None,
+            headless: false,
+            help: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArgError {
+    Unknown(String),
+    MissingValue(String),
+    InvalidValue { flag: String, value: String },
+}
+
+impl fmt::Display for ArgError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArgError::Unknown(arg) => write!(f, "unknown argument '{}'", arg),
+            ArgError::MissingValue(flag) => write!(f, "{} needs a value", flag),
+            ArgError::InvalidValue { flag, value } => {
+                write!(f, "invalid value '{}' for {}", value, flag)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ArgError {}
+
+// Parse the arguments without the program name
+pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Options, ArgError> {
+    let mut options = Options::default();
+    let mut args = args.into_iter();
+
+    while let Some(flag) = args.next() {
+        if flag == "--headless" {
+            options.headless = true;
+            continue;
+        }
+        if flag == "--help" || flag == "-h" {
+            options.help = true;
+            continue;
+        }
+
+        let value = match flag.as_str() {
+            "--size" | "--generations" | "--delay" | "--pattern" | "--seed" => args
+                .next()
+                .ok_or_else(|| ArgError::MissingValue(flag.clone()))?,
+            _ => return Err(ArgError::Unknown(flag)),
+        };
+        let invalid = || ArgError::InvalidValue {
+            flag: flag.clone(),
+            value: value.clone(),
+        };
+
+        match flag.as_str() {
+            "--size" => {
+                options.size = match value.as_str() {
+                    "small" => Size::Small,
+                    "medium" => Size::Medium,
+                    "large" => Size::Large,
+                    _ => return Err(invalid()),
+                }
+            }
+            "--generations" => options.generations = Some(value.parse().map_err(|_| invalid())?),
+            "--delay" => options.delay = value.parse().map_err(|_| invalid())?,
+            "--pattern" => {
+                if pattern_by_name(&value).is_none() {
+                    return Err(invalid());
+                }
+                options.pattern = Some(value);
+            }
+            "--seed" => options.seed = Some(value.parse().map_err(|_| invalid())?),
+            _ => unreachable!(),
+        }
+    }
+
+    Ok(options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse(args("")), Ok(Options::default()));
+
+        let options = parse(args(
+            "--size large --generations 50 --delay 0 --pattern glider --seed 7 --headless",
+        ))
+        .unwrap();
+        assert_eq!(
+            options,
+            Options {
+                size: Size::Large,
+                generations: Some(50),
+                delay: 0,
+                pattern: Some("glider".to_string()),
+                seed: Some(7),
+                headless: true,
+                help: false,
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            parse(args("--fast")),
+            Err(ArgError::Unknown("--fast".to_string()))
+        );
+        assert_eq!(
+            parse(args("--seed")),
+            Err(ArgError::MissingValue("--seed".to_string()))
+        );
+        assert_eq!(
+            parse(args("--generations many")),
+            Err(ArgError::InvalidValue {
+                flag: "--generations".to_string(),
+                value: "many".to_string()
+            })
+        );
+        assert!(parse(args("--pattern spaceship")).is_err());
+        assert!(parse(args("--size huge")).is_err());
+    }
+}
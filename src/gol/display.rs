@@ -1,55 +1,685 @@
-use crate::gol::engine::{Engine /* EngineRef */};
+use crate::gol::engine::{Engine, EngineRef};
 
-use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::fmt;
+use std::marker::PhantomData;
+use std::time::{Duration, Instant};
 
-use minifb::{Window, WindowOptions};
+use minifb::{Key, KeyRepeat, MouseButton, MouseMode, Window, WindowOptions};
 
-const COLOR_ALIVE: u32 = 0xFFFFFF; // White
-const COLOR_DEAD: u32 = 0x000000; // Black
-const SCALE: usize = 10; // Upscaling factor
+pub const COLOR_ALIVE: u32 = 0xFFFFFF; // White
+pub const COLOR_DEAD: u32 = 0x000000; // Black
+pub const COLOR_GRID: u32 = 0x404040; // Dark gray
+pub const SCALE: usize = 10; // Upscaling factor
+pub const MIN_GRID_SCALE: usize = 4; // Smaller cells are drawn without grid lines
+pub const TITLE: &str = "Conway's Game of Life";
+pub const DELAY_STEP: usize = 5; // Milliseconds added or removed by - and +
+pub const FPS_WINDOW: usize = 30; // Frames the frame rate is averaged over
+pub const COLOR_DIGIT: u32 = 0x808080; // Gray, readable on alive and dead cells
+pub const MIN_DIGIT_SCALE: usize = 7; // Smaller cells are drawn without neighbour counts
+pub const AGE_SPAN: u16 = 50; // Age at which a cell reaches the old end of the age gradient
+pub const MAX_FRAME_BUDGET: Duration = Duration::from_secs(60); // Slowest frame set_target_fps aims for
 
-pub struct Display<'a, const H: usize, const W: usize> {
-    engine: &'a RefCell<Engine<H, W>>,
-    window: Window,
-    delay: usize,
+#[derive(Debug)]
+pub enum DisplayError {
+    // The window was closed, e.g. from the title bar
+    Closed,
+    Window(minifb::Error),
 }
 
-impl<'a, const H: usize, const W: usize> Display<'a, H, W> {
-    pub fn new(engine: &'a RefCell<Engine<H, W>>, delay: usize) -> Self {
-        let window = Window::new(
-            "Conway's Game of Life",
-            W * SCALE,
-            H * SCALE,
-            WindowOptions::default(),
-        )
-        .unwrap();
+impl fmt::Display for DisplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DisplayError::Closed => write!(f, "window was closed"),
+            DisplayError::Window(err) => write!(f, "window error: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for DisplayError {}
+
+impl From<minifb::Error> for DisplayError {
+    fn from(err: minifb::Error) -> Self {
+        DisplayError::Window(err)
+    }
+}
+
+// Keyboard controls of Display::run:
+//  Space   pause / resume
+//  Right   advance a single generation while paused
+//  +       shorten the delay between frames by DELAY_STEP
+//  -       lengthen the delay between frames by DELAY_STEP
+//  R       restart from the seeded board, see Engine::restart
+//  Click   toggle the cell under the cursor
+//  Escape  quit
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Action {
+    Advance,
+    Redraw,
+    Restart,
+    // Switch to the next rule preset, see Rule::next_preset
+    NextRule,
+    Quit,
+}
+
+impl Action {
+    // Apply the action to the engine, Redraw and Quit leave it untouched
+    fn apply<const H: usize, const W: usize>(self, engine: &mut Engine<H, W>) {
+        match self {
+            Action::Advance => engine.generate(),
+            Action::Restart => engine.restart(),
+            Action::NextRule => engine.set_rule(engine.rule().next_preset()),
+            Action::Redraw | Action::Quit => {}
+        }
+    }
+}
+
+// Advance the engine by `frames` generations without a window,
+// calling on_frame after each one like Display::run_with does
+pub fn run_headless<const H: usize, const W: usize>(
+    engine: EngineRef<'_, H, W>,
+    frames: usize,
+    mut on_frame: impl FnMut(usize, &Engine<H, W>),
+) {
+    for _ in 0..frames {
+        Action::Advance.apply(&mut engine.borrow_mut());
+
+        let engine = engine.borrow();
+        on_frame(engine.generation(), &engine);
+    }
+}
+
+// Update the pause state and delay from the keys pressed this frame
+// and decide what the frame should do
+fn handle_keys(paused: &mut bool, delay: &mut usize, pressed: impl Fn(Key) -> bool) -> Action {
+    if pressed(Key::Escape) {
+        return Action::Quit;
+    }
+    if pressed(Key::R) {
+        return Action::Restart;
+    }
+    if pressed(Key::B) {
+        return Action::NextRule;
+    }
+
+    if pressed(Key::Equal) || pressed(Key::NumPadPlus) {
+        *delay = delay.saturating_sub(DELAY_STEP);
+    }
+    if pressed(Key::Minus) || pressed(Key::NumPadMinus) {
+        *delay += DELAY_STEP;
+    }
+
+    if pressed(Key::Space) {
+        *paused = !*paused;
+    }
+
+    if !*paused || pressed(Key::Right) {
+        Action::Advance
+    } else {
+        Action::Redraw
+    }
+}
+
+// Time per frame to reach `fps` frames per second, at most MAX_FRAME_BUDGET.
+// Panics unless `fps` is a positive number.
+fn frame_budget(fps: f64) -> Duration {
+    assert!(
+        fps.is_finite() && fps > 0.0,
+        "the target frame rate must be positive, got {}",
+        fps
+    );
+    Duration::try_from_secs_f64(1.0 / fps)
+        .map_or(MAX_FRAME_BUDGET, |budget| budget.min(MAX_FRAME_BUDGET))
+}
+
+// How long to sleep after a frame that took `elapsed` to fit a frame `budget`,
+// zero when the frame already took longer
+fn frame_sleep(budget: Duration, elapsed: Duration) -> Duration {
+    budget.saturating_sub(elapsed)
+}
 
+// Generation, population and the size of the bounding box of the alive cells,
+// e.g. "gen 12, pop 5, 3x3"
+fn stats_title<const H: usize, const W: usize>(engine: &Engine<H, W>) -> String {
+    let mut stats = format!("gen {}, pop {}", engine.generation(), engine.population());
+    if let Some((min_x, min_y, max_x, max_y)) = engine.cells().bounding_box() {
+        stats += &format!(", {}x{}", max_x - min_x + 1, max_y - min_y + 1);
+    }
+    stats
+}
+
+// Rolling frame and generation rate over the last `capacity` frames
+#[derive(Debug, Clone)]
+pub struct FrameCounter {
+    frames: VecDeque<(Instant, usize)>,
+    capacity: usize,
+}
+
+impl FrameCounter {
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity >= 2, "a rate needs at least two frames");
         Self {
-            engine,
-            window,
-            delay,
+            frames: VecDeque::with_capacity(capacity),
+            capacity,
         }
     }
 
-    pub fn update(&mut self) {
-        let mut buffer: Vec<u32> = vec![0; W * H];
-        let engine = self.engine.borrow();
+    // Record a frame drawn at `now` showing `generation`.
+    // Going back to an earlier generation (restart, undo) starts the rates over.
+    pub fn tick(&mut self, now: Instant, generation: usize) {
+        if self
+            .frames
+            .back()
+            .is_some_and(|&(_, last)| generation < last)
+        {
+            self.frames.clear();
+        }
+        if self.frames.len() == self.capacity {
+            self.frames.pop_front();
+        }
+        self.frames.push_back((now, generation));
+    }
+
+    // Frames per second, 0 until two frames were recorded
+    pub fn fps(&self) -> f64 {
+        self.per_second(self.frames.len().saturating_sub(1))
+    }
+
+    // Generations per second, lower than fps while paused
+    pub fn generations_per_second(&self) -> f64 {
+        match (self.frames.front(), self.frames.back()) {
+            (Some(&(_, first)), Some(&(_, last))) => self.per_second(last.saturating_sub(first)),
+            _ => 0.0,
+        }
+    }
+
+    // Rate of `count` events over the recorded frames
+    fn per_second(&self, count: usize) -> f64 {
+        let (Some(&(start, _)), Some(&(end, _))) = (self.frames.front(), self.frames.back()) else {
+            return 0.0;
+        };
 
+        let elapsed = end.duration_since(start).as_secs_f64();
+        if elapsed == 0.0 {
+            return 0.0;
+        }
+        count as f64 / elapsed
+    }
+}
+
+// Named (alive, dead) color pairs for DisplayBuilder::theme
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum DisplayTheme {
+    // White on black
+    #[default]
+    Classic,
+    // Black on white
+    Inverted,
+    // Green on black
+    Matrix,
+}
+
+impl DisplayTheme {
+    // (alive, dead)
+    pub fn colors(self) -> (u32, u32) {
+        match self {
+            DisplayTheme::Classic => (COLOR_ALIVE, COLOR_DEAD),
+            DisplayTheme::Inverted => (COLOR_DEAD, COLOR_ALIVE),
+            DisplayTheme::Matrix => (0x00FF41, COLOR_DEAD),
+        }
+    }
+}
+
+// How an alive cell fills its scale x scale block.
+// A Circle leaves the corners of the block in the dead color.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum CellShape {
+    #[default]
+    Square,
+    Circle,
+}
+
+// Whether pixel (px, py) of a scale x scale block lies in its inscribed circle,
+// measured from the pixel centers
+fn in_circle(px: usize, py: usize, scale: usize) -> bool {
+    let radius = scale as f32 / 2.0;
+    let dx = px as f32 + 0.5 - radius;
+    let dy = py as f32 + 0.5 - radius;
+    dx * dx + dy * dy <= radius * radius
+}
+
+// 3x5 bitmap font for the digits 0 to 8, one row per entry,
+// the highest of the 3 bits is the leftmost pixel
+#[rustfmt::skip]
+const DIGITS: [[u8; 5]; 9] = [
+    [0b111, 0b101, 0b101, 0b101, 0b111],
+    [0b010, 0b110, 0b010, 0b010, 0b111],
+    [0b111, 0b001, 0b111, 0b100, 0b111],
+    [0b111, 0b001, 0b011, 0b001, 0b111],
+    [0b101, 0b101, 0b111, 0b001, 0b001],
+    [0b111, 0b100, 0b111, 0b001, 0b111],
+    [0b111, 0b100, 0b111, 0b101, 0b111],
+    [0b111, 0b001, 0b010, 0b010, 0b010],
+    [0b111, 0b101, 0b111, 0b101, 0b111],
+];
+
+// Draw `digit` in `color` with its top left pixel at buffer[top * stride + left]
+fn draw_digit(buffer: &mut [u32], stride: usize, left: usize, top: usize, digit: u8, color: u32) {
+    for (row, bits) in DIGITS[digit as usize].iter().enumerate() {
+        for col in 0..3 {
+            if bits & (0b100 >> col) != 0 {
+                buffer[(top + row) * stride + left + col] = color;
+            }
+        }
+    }
+}
+
+// Controls how the cells are drawn into the window buffer
+#[derive(Debug, Copy, Clone)]
+pub struct DisplayConfig {
+    pub scale: usize,
+    pub color_alive: u32,
+    pub color_dead: u32,
+    pub show_grid: bool,
+    // (young, old) colors alive cells fade between as they age,
+    // color_alive is used for every alive cell when None
+    pub age_gradient: Option<(u32, u32)>,
+    pub shape: CellShape,
+    // Draw the stored neighbour count of every cell, ignored below MIN_DIGIT_SCALE
+    pub show_neighbours: bool,
+}
+
+impl Default for DisplayConfig {
+    fn default() -> Self {
+        Self {
+            scale: SCALE,
+            color_alive: COLOR_ALIVE,
+            color_dead: COLOR_DEAD,
+            show_grid: false,
+            age_gradient: None,
+            shape: CellShape::default(),
+            show_neighbours: false,
+        }
+    }
+}
+
+impl DisplayConfig {
+    // Cell under a window position in pixels, None outside the board
+    pub fn cell_at<const H: usize, const W: usize>(
+        &self,
+        x: f32,
+        y: f32,
+    ) -> Option<(isize, isize)> {
+        if x < 0.0 || y < 0.0 {
+            return None;
+        }
+
+        let (cx, cy) = (x as usize / self.scale, y as usize / self.scale);
+        if cx >= W || cy >= H {
+            return None;
+        }
+        Some((cx as isize, cy as isize))
+    }
+
+    // Draw every cell as a scale x scale block into a buffer
+    // of (W * scale) x (H * scale) pixels.
+    // With show_grid the outermost pixels of every block are COLOR_GRID.
+    pub fn render<const H: usize, const W: usize>(
+        &self,
+        engine: &Engine<H, W>,
+        buffer: &mut [u32],
+    ) {
         for y in 0..H {
             for x in 0..W {
-                let color = {
-                    let cell = engine.cells().cell(x as isize, y as isize);
-                    if cell.alive() {
-                        COLOR_ALIVE
+                self.render_cell(engine, buffer, x, y);
+            }
+        }
+    }
+
+    // Redraw only the cells that changed during the last generation,
+    // the buffer must hold the render of the generation before
+    pub fn render_changes<const H: usize, const W: usize>(
+        &self,
+        engine: &Engine<H, W>,
+        buffer: &mut [u32],
+    ) {
+        for (x, y) in engine.changed_since_last_generation() {
+            self.render_cell(engine, buffer, x, y);
+        }
+    }
+
+    fn render_cell<const H: usize, const W: usize>(
+        &self,
+        engine: &Engine<H, W>,
+        buffer: &mut [u32],
+        x: usize,
+        y: usize,
+    ) {
+        let stride = W * self.scale;
+        let grid = self.show_grid && self.scale >= MIN_GRID_SCALE;
+        let (cx, cy) = (x as isize, y as isize);
+        let alive = engine.cells().cell(cx, cy).alive();
+        let color = match self.age_gradient {
+            _ if !alive => self.color_dead,
+            Some((young, old)) => blend(young, old, engine.cell_age(cx, cy), AGE_SPAN),
+            None => self.color_alive,
+        };
+
+        let circle = alive && self.shape == CellShape::Circle;
+
+        for py in y * self.scale..(y + 1) * self.scale {
+            let row = py * stride;
+            let block = &mut buffer[row + x * self.scale..row + (x + 1) * self.scale];
+
+            if circle {
+                for (px, pixel) in block.iter_mut().enumerate() {
+                    *pixel = if in_circle(px, py - y * self.scale, self.scale) {
+                        color
                     } else {
-                        COLOR_DEAD
-                    }
-                };
-                buffer[y * W + x] = color;
+                        self.color_dead
+                    };
+                }
+            } else {
+                block.fill(color);
+            }
+
+            if !grid {
+                continue;
+            } else if py == y * self.scale || py == (y + 1) * self.scale - 1 {
+                block.fill(COLOR_GRID);
+            } else {
+                block[0] = COLOR_GRID;
+                block[self.scale - 1] = COLOR_GRID;
+            }
+        }
+
+        if self.show_neighbours && self.scale >= MIN_DIGIT_SCALE {
+            let neighbours = engine.cells().cell(cx, cy).neighbours();
+            let left = x * self.scale + (self.scale - 3) / 2;
+            let top = y * self.scale + (self.scale - 5) / 2;
+            draw_digit(buffer, stride, left, top, neighbours, COLOR_DIGIT);
+        }
+    }
+}
+
+// Color `age / span` of the way from `from` to `to`, channel by channel
+fn blend(from: u32, to: u32, age: u16, span: u16) -> u32 {
+    let t = age.min(span) as u32;
+    let span = span.max(1) as u32;
+    [16, 8, 0].iter().fold(0, |color, shift| {
+        let a = (from >> shift) & 0xFF;
+        let b = (to >> shift) & 0xFF;
+        let c = (a * (span - t) + b * t) / span;
+        color | (c << shift)
+    })
+}
+
+pub struct DisplayBuilder<'a, const H: usize, const W: usize> {
+    config: DisplayConfig,
+    title: String,
+    delay: usize,
+    show_stats: bool,
+    engine: PhantomData<EngineRef<'a, H, W>>,
+}
+
+impl<'a, const H: usize, const W: usize> Default for DisplayBuilder<'a, H, W> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, const H: usize, const W: usize> DisplayBuilder<'a, H, W> {
+    pub fn new() -> Self {
+        Self {
+            config: DisplayConfig::default(),
+            title: TITLE.to_string(),
+            delay: 0,
+            show_stats: false,
+            engine: PhantomData,
+        }
+    }
+
+    pub fn scale(mut self, scale: usize) -> Self {
+        self.config.scale = scale;
+        self
+    }
+
+    pub fn color_alive(mut self, color: u32) -> Self {
+        self.config.color_alive = color;
+        self
+    }
+
+    pub fn color_dead(mut self, color: u32) -> Self {
+        self.config.color_dead = color;
+        self
+    }
+
+    // Sets both the alive and the dead color
+    pub fn theme(mut self, theme: DisplayTheme) -> Self {
+        (self.config.color_alive, self.config.color_dead) = theme.colors();
+        self
+    }
+
+    // Draw grid lines between the cells, ignored below MIN_GRID_SCALE
+    pub fn show_grid(mut self, show_grid: bool) -> Self {
+        self.config.show_grid = show_grid;
+        self
+    }
+
+    // Fade alive cells from `young` to `old` over AGE_SPAN generations
+    pub fn age_gradient(mut self, young: u32, old: u32) -> Self {
+        self.config.age_gradient = Some((young, old));
+        self
+    }
+
+    pub fn shape(mut self, shape: CellShape) -> Self {
+        self.config.shape = shape;
+        self
+    }
+
+    // Draw every cell's stored neighbour count, for debugging the bookkeeping
+    pub fn show_neighbours(mut self, show_neighbours: bool) -> Self {
+        self.config.show_neighbours = show_neighbours;
+        self
+    }
+
+    pub fn title(mut self, title: &str) -> Self {
+        self.title = title.to_string();
+        self
+    }
+
+    // Add the generation, population and bounding box size to the window title,
+    // see stats_title
+    pub fn show_stats(mut self, show_stats: bool) -> Self {
+        self.show_stats = show_stats;
+        self
+    }
+
+    // Delay between frames in milliseconds
+    pub fn delay(mut self, delay: usize) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    // Width and height of the window buffer in pixels
+    pub fn dimensions(&self) -> (usize, usize) {
+        (W * self.config.scale, H * self.config.scale)
+    }
+
+    // Fails with DisplayError::Window when no window can be opened,
+    // e.g. without a display server
+    pub fn build(self, engine: EngineRef<'a, H, W>) -> Result<Display<'a, H, W>, DisplayError> {
+        let (width, height) = self.dimensions();
+        let window = Window::new(&self.title, width, height, WindowOptions::default())?;
+
+        Ok(Display {
+            engine,
+            window,
+            delay: self.delay,
+            frame_budget: None,
+            frame_end: Instant::now(),
+            paused: false,
+            mouse_down: false,
+            drawn_generation: None,
+            frames: FrameCounter::new(FPS_WINDOW),
+            title: self.title,
+            show_stats: self.show_stats,
+            config: self.config,
+            buffer: vec![0; width * height],
+        })
+    }
+}
+
+pub struct Display<'a, const H: usize, const W: usize> {
+    engine: EngineRef<'a, H, W>,
+    window: Window,
+    delay: usize,
+    // Time per frame set by set_target_fps, replaces the fixed delay
+    frame_budget: Option<Duration>,
+    // When the previous frame finished, frames are timed from there
+    frame_end: Instant,
+    paused: bool,
+    mouse_down: bool,
+    // Generation shown in the buffer, None when it has to be redrawn in full
+    drawn_generation: Option<usize>,
+    frames: FrameCounter,
+    title: String,
+    show_stats: bool,
+    config: DisplayConfig,
+    buffer: Vec<u32>,
+}
+
+impl<'a, const H: usize, const W: usize> Display<'a, H, W> {
+    pub fn new(engine: EngineRef<'a, H, W>, delay: usize) -> Result<Self, DisplayError> {
+        DisplayBuilder::new().delay(delay).build(engine)
+    }
+
+    // Aim for `fps` frames per second instead of waiting a fixed delay after every frame.
+    // Only the time left after computing and drawing a frame is slept,
+    // changing the delay with + or - goes back to the fixed delay.
+    // Rates below one frame per MAX_FRAME_BUDGET are clamped to it.
+    // Panics unless `fps` is a positive number.
+    pub fn set_target_fps(&mut self, fps: f64) {
+        self.frame_budget = Some(frame_budget(fps));
+    }
+
+    // Draw the engine and wait for the delay, or until the frame budget is used up.
+    // A frame is timed from the end of the previous update.
+    // Fails with DisplayError::Closed once the window has been closed.
+    pub fn update(&mut self) -> Result<(), DisplayError> {
+        if !self.window.is_open() {
+            return Err(DisplayError::Closed);
+        }
+
+        // Only the changed cells need drawing when the buffer shows the previous generation,
+        // unless surviving cells change color as they age or neighbour counts are shown
+        let engine = self.engine.borrow();
+        if self.config.age_gradient.is_none()
+            && !self.config.show_neighbours
+            && self
+                .drawn_generation
+                .is_some_and(|drawn| drawn + 1 == engine.generation())
+        {
+            self.config.render_changes(&engine, &mut self.buffer);
+        } else if self.drawn_generation != Some(engine.generation()) {
+            self.config.render(&engine, &mut self.buffer);
+        }
+        self.drawn_generation = Some(engine.generation());
+        drop(engine);
+
+        let (width, height) = (W * self.config.scale, H * self.config.scale);
+        self.window
+            .update_with_buffer(&self.buffer, width, height)?;
+        let sleep = match self.frame_budget {
+            Some(budget) => frame_sleep(budget, self.frame_end.elapsed()),
+            None => Duration::from_millis(self.delay as u64),
+        };
+        std::thread::sleep(sleep);
+        self.frame_end = Instant::now();
+        Ok(())
+    }
+
+    // Frames drawn per second by run, averaged over the last FPS_WINDOW frames
+    pub fn current_fps(&self) -> f64 {
+        self.frames.fps()
+    }
+
+    // Generations computed per second by run, averaged like current_fps
+    pub fn generations_per_second(&self) -> f64 {
+        self.frames.generations_per_second()
+    }
+
+    // Toggle the cell under the cursor when the left button goes down,
+    // holding the button does not toggle the cell again
+    fn handle_mouse(&mut self) {
+        let down = self.window.get_mouse_down(MouseButton::Left);
+        let clicked = down && !self.mouse_down;
+        self.mouse_down = down;
+        if !clicked {
+            return;
+        }
+
+        let Some((x, y)) = self
+            .window
+            .get_mouse_pos(MouseMode::Discard)
+            .and_then(|(x, y)| self.config.cell_at::<H, W>(x, y))
+        else {
+            return;
+        };
+
+        self.engine.borrow_mut().toggle_cell(x, y);
+        self.drawn_generation = None;
+    }
+
+    // Advance and draw the engine until the window is closed or Escape is pressed,
+    // see Action for the keyboard controls. Closing the window is not an error.
+    pub fn run(&mut self) -> Result<(), DisplayError> {
+        self.run_with(|_, _| {})
+    }
+
+    // Same as run, calling on_frame with the generation and the engine
+    // after every frame has been drawn
+    pub fn run_with(
+        &mut self,
+        mut on_frame: impl FnMut(usize, &Engine<H, W>),
+    ) -> Result<(), DisplayError> {
+        while self.window.is_open() {
+            self.handle_mouse();
+
+            let window = &self.window;
+            let delay = self.delay;
+            let action = handle_keys(&mut self.paused, &mut self.delay, |key| {
+                window.is_key_pressed(key, KeyRepeat::No)
+            });
+            if self.delay != delay {
+                self.frame_budget = None;
+            }
+
+            if action == Action::Quit {
+                break;
+            }
+            action.apply(&mut self.engine.borrow_mut());
+
+            match self.update() {
+                Err(DisplayError::Closed) => break,
+                result => result?,
+            }
+
+            let engine = self.engine.borrow();
+            self.frames.tick(Instant::now(), engine.generation());
+            let mut title = format!(
+                "{} - {:.1} fps, {:.1} gen/s",
+                self.title,
+                self.frames.fps(),
+                self.frames.generations_per_second()
+            );
+            if self.show_stats {
+                title += &format!(" - {}", stats_title(&engine));
             }
+            self.window.set_title(&title);
+            on_frame(engine.generation(), &engine);
         }
-        self.window.update_with_buffer(&buffer, W, H).unwrap();
-        std::thread::sleep(std::time::Duration::from_millis(self.delay as u64));
+        Ok(())
     }
 }
 
@@ -57,7 +687,394 @@ impl<'a, const H: usize, const W: usize> Display<'a, H, W> {
 mod tests {
 
     use super::*;
-    use std::time::Instant;
+    use std::cell::RefCell;
+    use std::time::Duration;
+
+    #[test]
+    fn test_render_orientation() {
+        // 20 rows, 40 columns
+        const H: usize = 20;
+        const W: usize = 40;
+        const SCALE: usize = 2;
+        let mut engine = Engine::<H, W>::new();
+        // An L: a vertical bar down from (30, 2) with a foot to the right
+        engine.spawn_cells(&[(30, 2), (30, 3), (30, 4), (31, 4)]);
+
+        let config = DisplayConfig {
+            scale: SCALE,
+            ..DisplayConfig::default()
+        };
+        let (width, height) = DisplayBuilder::<H, W>::new().scale(SCALE).dimensions();
+        assert_eq!((width, height), (80, 40));
+
+        let mut buffer = vec![0; width * height];
+        config.render(&engine, &mut buffer);
+        let pixel = |x: usize, y: usize| buffer[y * SCALE * width + x * SCALE];
+
+        for (x, y) in [(30, 2), (30, 3), (30, 4), (31, 4)] {
+            assert_eq!(pixel(x, y), COLOR_ALIVE, "({}, {})", x, y);
+        }
+        // The transposed L would be here
+        assert_eq!(pixel(2, 30 % H), COLOR_DEAD);
+        assert_eq!(pixel(31, 2), COLOR_DEAD);
+        assert_eq!(
+            buffer.iter().filter(|&&p| p == COLOR_ALIVE).count(),
+            4 * SCALE * SCALE
+        );
+    }
+
+    #[test]
+    fn test_render_changes() {
+        let mut engine = Engine::<6, 6>::new();
+        engine.spawn_cells(&[(1, 2), (2, 2), (3, 2)]);
+        let config = DisplayConfig::default();
+        let mut partial = vec![0; 36 * SCALE * SCALE];
+        config.render(&engine, &mut partial);
+
+        for _ in 0..3 {
+            engine.generate();
+            config.render_changes(&engine, &mut partial);
+
+            let mut full = vec![0; 36 * SCALE * SCALE];
+            config.render(&engine, &mut full);
+            assert!(partial == full);
+        }
+    }
+
+    #[test]
+    fn test_grid_lines() {
+        const SCALE: usize = 5;
+        let mut engine = Engine::<2, 2>::new();
+        engine.cells.spawn(1, 0);
+
+        let config = DisplayConfig {
+            scale: SCALE,
+            show_grid: true,
+            ..DisplayConfig::default()
+        };
+        let mut buffer = vec![0; 2 * SCALE * 2 * SCALE];
+        config.render(&engine, &mut buffer);
+
+        // Block of the alive cell (1, 0)
+        let stride = 2 * SCALE;
+        for py in 0..SCALE {
+            for px in SCALE..2 * SCALE {
+                let border = py == 0 || py == SCALE - 1 || px == SCALE || px == 2 * SCALE - 1;
+                let expected = if border { COLOR_GRID } else { COLOR_ALIVE };
+                assert_eq!(buffer[py * stride + px], expected, "pixel ({}, {})", px, py);
+            }
+        }
+        // Interior of the dead cell (0, 1)
+        assert_eq!(buffer[(SCALE + 2) * stride + 2], COLOR_DEAD);
+
+        // Too small for grid lines
+        let config = DisplayConfig {
+            scale: MIN_GRID_SCALE - 1,
+            ..config
+        };
+        let mut buffer = vec![0; 4 * config.scale * config.scale];
+        config.render(&engine, &mut buffer);
+        assert!(!buffer.contains(&COLOR_GRID));
+    }
+
+    #[test]
+    fn test_theme() {
+        let mut engine = Engine::<1, 2>::new();
+        engine.spawn_cells(&[(0, 0)]);
+        let render = |theme: DisplayTheme| {
+            let builder = DisplayBuilder::<1, 2>::new().scale(1).theme(theme);
+            let mut buffer = vec![0; 2];
+            builder.config.render(&engine, &mut buffer);
+            buffer
+        };
+
+        let (alive, dead) = DisplayTheme::Classic.colors();
+        assert_eq!(render(DisplayTheme::default()), [alive, dead]);
+        assert_eq!(render(DisplayTheme::Inverted), [dead, alive]);
+        assert_eq!(render(DisplayTheme::Matrix), [0x00FF41, COLOR_DEAD]);
+    }
+
+    #[test]
+    fn test_draw_digit() {
+        let render = |digit: u8| {
+            let mut buffer = vec![0; 5 * 7];
+            draw_digit(&mut buffer, 5, 1, 1, digit, 1);
+            buffer
+                .chunks(5)
+                .map(|row| {
+                    row.iter()
+                        .map(|&p| if p == 1 { '#' } else { '.' })
+                        .collect()
+                })
+                .collect::<Vec<String>>()
+        };
+
+        assert_eq!(
+            render(1),
+            [".....", "..#..", ".##..", "..#..", "..#..", ".###.", "....."]
+        );
+        assert_eq!(
+            render(8),
+            [".....", ".###.", ".#.#.", ".###.", ".#.#.", ".###.", "....."]
+        );
+    }
+
+    #[test]
+    fn test_neighbour_overlay() {
+        const SCALE: usize = MIN_DIGIT_SCALE;
+        let mut engine = Engine::<3, 3>::new();
+        engine.spawn_cells(&[(0, 0)]);
+        let config = DisplayConfig {
+            scale: SCALE,
+            show_neighbours: true,
+            ..DisplayConfig::default()
+        };
+        let mut buffer = vec![0; 9 * SCALE * SCALE];
+        config.render(&engine, &mut buffer);
+
+        // On a 3x3 torus every other cell neighbours (0, 0): a 1 in the
+        // dead blocks, a 0 with its hole in the middle in the alive one
+        let stride = 3 * SCALE;
+        let middle =
+            |x: usize, y: usize| buffer[(y * SCALE + SCALE / 2) * stride + x * SCALE + SCALE / 2];
+        assert_eq!(middle(0, 0), COLOR_ALIVE);
+        assert_eq!(middle(1, 1), COLOR_DIGIT);
+        assert_eq!(middle(2, 0), COLOR_DIGIT);
+    }
+
+    #[test]
+    fn test_circle_shape() {
+        const SCALE: usize = 9;
+        let mut engine = Engine::<3, 3>::new();
+        engine.spawn_cells(&[(1, 1)]);
+        let config = DisplayConfig {
+            scale: SCALE,
+            shape: CellShape::Circle,
+            ..DisplayConfig::default()
+        };
+        let mut buffer = vec![0x123456; 9 * SCALE * SCALE];
+        config.render(&engine, &mut buffer);
+
+        let stride = 3 * SCALE;
+        let pixel = |px: usize, py: usize| buffer[(SCALE + py) * stride + SCALE + px];
+        for (px, py) in [
+            (0, 0),
+            (SCALE - 1, 0),
+            (0, SCALE - 1),
+            (SCALE - 1, SCALE - 1),
+        ] {
+            assert_eq!(pixel(px, py), COLOR_DEAD, "corner ({}, {})", px, py);
+        }
+        assert_eq!(pixel(SCALE / 2, SCALE / 2), COLOR_ALIVE);
+        // The circle touches the middle of every edge
+        assert_eq!(pixel(0, SCALE / 2), COLOR_ALIVE);
+        assert_eq!(pixel(SCALE / 2, SCALE - 1), COLOR_ALIVE);
+        assert!(!buffer.contains(&0x123456));
+    }
+
+    #[test]
+    fn test_age_gradient() {
+        assert_eq!(blend(0x000000, 0xFF8040, 0, AGE_SPAN), 0x000000);
+        assert_eq!(blend(0x000000, 0xFF8040, AGE_SPAN, AGE_SPAN), 0xFF8040);
+        assert_eq!(blend(0x000000, 0xFF8040, 2 * AGE_SPAN, AGE_SPAN), 0xFF8040);
+        assert_eq!(blend(0x000000, 0xFF8040, 5, 10), 0x7F4020);
+
+        // A blinker: the centre survives while the tips are newborn
+        let mut engine = Engine::<5, 5>::new();
+        engine.spawn_cells(&[(1, 2), (2, 2), (3, 2)]);
+        engine.step_n(AGE_SPAN as usize);
+        let config = DisplayConfig {
+            scale: 1,
+            age_gradient: Some((0x00FF00, 0xFF0000)),
+            ..DisplayConfig::default()
+        };
+        let mut buffer = vec![0; 25];
+        config.render(&engine, &mut buffer);
+        assert_eq!(buffer[2 * 5 + 2], 0xFF0000);
+        assert_eq!(buffer[2 * 5 + 1], 0x00FF00);
+        assert_eq!(buffer[0], COLOR_DEAD);
+    }
+
+    #[test]
+    fn test_display_error() {
+        let err = DisplayError::from(minifb::Error::WindowCreate("no display".to_string()));
+        assert!(matches!(err, DisplayError::Window(_)));
+        assert_eq!(DisplayError::Closed.to_string(), "window was closed");
+
+        // Usable as a boxed error next to other error types
+        let boxed: Box<dyn std::error::Error> = Box::new(err);
+        assert!(boxed.to_string().starts_with("window error: "));
+    }
+
+    #[test]
+    fn test_cell_at() {
+        let config = DisplayConfig::default();
+
+        assert_eq!(config.cell_at::<10, 20>(0.0, 0.0), Some((0, 0)));
+        assert_eq!(config.cell_at::<10, 20>(35.5, 99.9), Some((3, 9)));
+        assert_eq!(config.cell_at::<10, 20>(199.0, 0.0), Some((19, 0)));
+
+        assert_eq!(config.cell_at::<10, 20>(200.0, 0.0), None);
+        assert_eq!(config.cell_at::<10, 20>(0.0, 100.0), None);
+        assert_eq!(config.cell_at::<10, 20>(-1.0, 5.0), None);
+    }
+
+    #[test]
+    fn test_frame_counter() {
+        let mut counter = FrameCounter::new(4);
+        let start = Instant::now();
+        assert_eq!(counter.fps(), 0.0);
+
+        counter.tick(start, 0);
+        assert_eq!(counter.fps(), 0.0);
+
+        // 20 frames per second, a generation every other frame
+        for frame in 1..10u32 {
+            counter.tick(
+                start + Duration::from_millis(50) * frame,
+                frame as usize / 2,
+            );
+        }
+        assert!((counter.fps() - 20.0).abs() < 1e-9);
+        // The last 4 frames show generations 3, 3, 4, 4 over 150 ms
+        assert!((counter.generations_per_second() - 1.0 / 0.15).abs() < 1e-9);
+
+        // A restart only counts the generations after it
+        let restart = start + Duration::from_millis(500);
+        counter.tick(restart, 0);
+        assert_eq!(counter.generations_per_second(), 0.0);
+        counter.tick(restart + Duration::from_millis(100), 2);
+        assert!((counter.generations_per_second() - 20.0).abs() < 1e-9);
+        assert!((counter.fps() - 10.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_run_headless() {
+        let engine = RefCell::new(Engine::<6, 6>::new());
+        // An L tromino becomes a block and stays one
+        engine.borrow_mut().spawn_cells(&[(1, 1), (2, 1), (1, 2)]);
+
+        let mut populations = Vec::new();
+        run_headless(&engine, 3, |generation, engine| {
+            populations.push((generation, engine.population()));
+        });
+
+        assert_eq!(populations, vec![(1, 4), (2, 4), (3, 4)]);
+        assert_eq!(engine.borrow().generation(), 3);
+    }
+
+    #[test]
+    fn test_stats_title() {
+        let mut engine = Engine::<10, 10>::new();
+        assert_eq!(stats_title(&engine), "gen 0, pop 0");
+
+        engine.spawn_cells(&[(2, 3), (4, 3), (3, 6)]);
+        engine.generation = 12;
+        assert_eq!(stats_title(&engine), "gen 12, pop 3, 3x4");
+    }
+
+    #[test]
+    fn test_frame_sleep() {
+        let budget = Duration::from_secs_f64(1.0 / 50.0);
+        assert_eq!(frame_sleep(budget, Duration::ZERO), budget);
+        assert_eq!(
+            frame_sleep(budget, Duration::from_millis(5)),
+            Duration::from_millis(15)
+        );
+        assert_eq!(frame_sleep(budget, budget), Duration::ZERO);
+        assert_eq!(
+            frame_sleep(budget, Duration::from_millis(35)),
+            Duration::ZERO
+        );
+    }
+
+    #[test]
+    fn test_frame_budget() {
+        assert_eq!(frame_budget(50.0), Duration::from_millis(20));
+        assert_eq!(frame_budget(f64::MAX), Duration::ZERO);
+        // Slow rates stop at the longest budget instead of overflowing
+        assert_eq!(frame_budget(0.001), MAX_FRAME_BUDGET);
+        assert_eq!(frame_budget(f64::MIN_POSITIVE), MAX_FRAME_BUDGET);
+    }
+
+    #[test]
+    #[should_panic(expected = "frame rate must be positive")]
+    fn test_frame_budget_nan() {
+        frame_budget(f64::NAN);
+    }
+
+    #[test]
+    #[should_panic(expected = "frame rate must be positive")]
+    fn test_frame_budget_infinite() {
+        frame_budget(f64::INFINITY);
+    }
+
+    #[test]
+    fn test_handle_keys() {
+        let mut paused = false;
+        let mut delay = 10;
+        let none = |_| false;
+
+        assert_eq!(handle_keys(&mut paused, &mut delay, none), Action::Advance);
+
+        // Space pauses, frames only redraw until Right is pressed
+        let space = |key| key == Key::Space;
+        assert_eq!(handle_keys(&mut paused, &mut delay, space), Action::Redraw);
+        assert!(paused);
+        assert_eq!(handle_keys(&mut paused, &mut delay, none), Action::Redraw);
+        let right = |key| key == Key::Right;
+        assert_eq!(handle_keys(&mut paused, &mut delay, right), Action::Advance);
+        assert!(paused);
+
+        // Space resumes
+        assert_eq!(handle_keys(&mut paused, &mut delay, space), Action::Advance);
+        assert!(!paused);
+
+        // + and - change the delay, which never drops below zero
+        handle_keys(&mut paused, &mut delay, |key| key == Key::Minus);
+        assert_eq!(delay, 10 + DELAY_STEP);
+        for _ in 0..5 {
+            handle_keys(&mut paused, &mut delay, |key| key == Key::NumPadPlus);
+        }
+        assert_eq!(delay, 0);
+
+        assert_eq!(
+            handle_keys(&mut paused, &mut delay, |key| key == Key::R),
+            Action::Restart
+        );
+
+        assert_eq!(
+            handle_keys(&mut paused, &mut delay, |key| key == Key::B),
+            Action::NextRule
+        );
+
+        let escape = |key| key == Key::Escape;
+        assert_eq!(handle_keys(&mut paused, &mut delay, escape), Action::Quit);
+    }
+
+    #[test]
+    fn test_builder_dimensions() {
+        const H: usize = 50;
+        const W: usize = 40;
+        const SCALE: usize = 20;
+        const ALIVE: u32 = 0xFF0000;
+        const DEAD: u32 = 0x0000FF;
+
+        let builder = DisplayBuilder::<H, W>::new()
+            .scale(SCALE)
+            .color_alive(ALIVE)
+            .color_dead(DEAD)
+            .title("Test")
+            .delay(5);
+        let (width, height) = builder.dimensions();
+        assert_eq!((width, height), (W * SCALE, H * SCALE));
+
+        // The whole buffer is filled by the render
+        let engine = Engine::<H, W>::new();
+        let mut buffer = vec![ALIVE; width * height];
+        builder.config.render(&engine, &mut buffer);
+        assert!(buffer.iter().all(|&pixel| pixel == DEAD));
+    }
 
     #[test]
     fn test_display_time() {
@@ -69,13 +1086,18 @@ mod tests {
         let engine = RefCell::new(Engine::<H, W>::new());
         engine.borrow_mut().randomize();
 
-        //TODO: Builder pattern
-        let mut display = Display::<H, W>::new(&engine, DELAY);
+        let mut display = match Display::<H, W>::new(&engine, DELAY) {
+            Ok(display) => display,
+            Err(err) => {
+                println!("Skipping, no window available: {}", err);
+                return;
+            }
+        };
 
         let start = Instant::now();
         for _ in 0..GENERATIONS {
             engine.borrow_mut().generate();
-            display.update();
+            display.update().unwrap();
         }
         let duration = start.elapsed();
 
@@ -0,0 +1,81 @@
+use crate::gol::types::*;
+
+// Engine over a DynCellArray, for boards whose size is only known at runtime.
+// Applies B3/S23 like Engine::generate does by default, through Grid::step_into.
+pub struct DynEngine<C = Cell> {
+    cells: DynCellArray<C>,
+    cell_cache: DynCellArray<C>,
+    generation: usize,
+}
+
+impl<C: CellStorage> DynEngine<C> {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            cells: DynCellArray::new(width, height),
+            cell_cache: DynCellArray::new(width, height),
+            generation: 0,
+        }
+    }
+
+    // Compute the next generation into the back buffer and swap it to the front
+    pub fn generate(&mut self) {
+        self.cell_cache.reset();
+        self.cells
+            .step_into(&mut self.cell_cache, &Rule::conway(), |_, _| {});
+
+        std::mem::swap(&mut self.cells, &mut self.cell_cache);
+        self.generation += 1;
+    }
+
+    pub fn cells(&self) -> &DynCellArray<C> {
+        &self.cells
+    }
+
+    pub fn cells_mut(&mut self) -> &mut DynCellArray<C> {
+        &mut self.cells
+    }
+
+    // Number of generations computed so far
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    // Number of alive cells on the current board
+    pub fn population(&self) -> usize {
+        self.cells.population()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gol::engine::Engine;
+    use crate::gol::patterns::{Glider, Pattern};
+
+    #[test]
+    fn test_matches_engine() {
+        let mut dyn_engine = DynEngine::<Cell>::new(8, 6);
+        let mut engine = Engine::<6, 8>::new();
+        for &(x, y) in Glider.cells() {
+            dyn_engine.cells_mut().spawn(x + 1, y + 1);
+            engine.cells.spawn(x + 1, y + 1);
+        }
+
+        // Long enough for the glider to wrap around both edges
+        for _ in 0..40 {
+            dyn_engine.generate();
+            engine.generate();
+
+            for y in 0..6 {
+                for x in 0..8 {
+                    assert_eq!(
+                        dyn_engine.cells().cell(x, y).raw(),
+                        engine.cells().cell(x, y).raw()
+                    );
+                }
+            }
+        }
+        assert_eq!(dyn_engine.generation(), 40);
+        assert_eq!(dyn_engine.population(), 5);
+    }
+}
@@ -1,83 +1,2272 @@
+use crate::gol::patterns::{Beehive, Blinker, Block, Loaf, Pattern};
 use crate::gol::types::*;
-pub struct Engine<const H: usize, const W: usize> {
-    cells: CellArray<H, W>,
-    cell_cache: CellArray<H, W>,
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::cell::RefCell;
+use std::collections::{HashSet, VecDeque};
+
+// Outcome of Engine::generate_until_stable.
+// `period` is 1 for still lifes, 2 for blinkers, etc. and 0 if no cycle was found.
+// `generation` is the generation at which the cycle was detected,
+// or the last generation computed if the board never stabilized.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct StabilityResult {
+    pub stabilized: bool,
+    pub period: usize,
+    pub generation: usize,
+}
+
+// Cells that changed during one generation, as (x, y) row by row.
+// Returned by Engine::generate_with_events.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GenerationEvents {
+    pub born: Vec<(usize, usize)>,
+    pub died: Vec<(usize, usize)>,
+}
+
+// State after one Engine::tick.
+// `stable` is set when the tick left every cell alive or dead as it was,
+// e.g. a still life, which includes an extinct board.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct TickSummary {
+    pub generation: usize,
+    pub population: usize,
+    pub stable: bool,
+    pub extinct: bool,
+}
+
+// (births, deaths) per neighbour count, for every count a rule can refer to.
+// Returned by Engine::transition_breakdown.
+pub type TransitionBreakdown = [(usize, usize); MAX_RULE_NEIGHBOURS as usize + 1];
+
+// Number of each kind of object on the board, see Engine::classify_objects.
+// Blinkers are counted in either phase, `unknown` holds every other cluster.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct ObjectCensus {
+    pub blocks: usize,
+    pub beehives: usize,
+    pub loaves: usize,
+    pub blinkers: usize,
+    pub unknown: usize,
+}
+
+// Outcome of one random soup run by Engine::search_soups.
+// `lifespan` is the generation at which the soup settled into a cycle,
+// or the number of generations run if it never did.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SoupTrial {
+    pub seed: u64,
+    pub population: usize,
+    pub stabilized: bool,
+    pub lifespan: usize,
+}
+
+// Every trial of Engine::search_soups in order, and the longest lived one
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SoupResult {
+    pub best: SoupTrial,
+    pub trials: Vec<SoupTrial>,
+}
+
+// Longest cycle search_soups recognizes as stable, enough for the common oscillators
+pub const SOUP_MAX_PERIOD: usize = 30;
+
+// Default number of populations kept by Engine::history
+pub const HISTORY_CAPACITY: usize = 1024;
+
+// Mirror axes used by Engine::randomize_symmetric.
+// Horizontal mirrors left and right, (x, y) <-> (W - 1 - x, y),
+// Vertical mirrors top and bottom, (x, y) <-> (x, H - 1 - y).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Symmetry {
+    Horizontal,
+    Vertical,
+    Both,
 }
 
-impl<const H: usize, const W: usize> Engine<H, W> {
+// State of a gradual seeding started by Engine::drip_seed
+struct Drip {
+    rng: StdRng,
+    cells_per_gen: usize,
+    remaining: usize,
+    injected: usize,
+}
+
+// Frozen copy of a board taken by Engine::snapshot.
+// Read only, so it can be handed to a renderer or another thread
+// while the engine keeps generating.
+#[derive(Debug, Clone)]
+pub struct BoardSnapshot<const H: usize, const W: usize, C = Cell> {
+    cells: Box<CellArray<H, W, C>>,
+    generation: usize,
+}
+
+impl<const H: usize, const W: usize, C: CellStorage> BoardSnapshot<H, W, C> {
+    pub fn cell(&self, x: isize, y: isize) -> &C {
+        self.cells.cell(x, y)
+    }
+
+    pub fn population(&self) -> usize {
+        self.cells.population()
+    }
+
+    pub fn live_coords(&self) -> impl Iterator<Item = (isize, isize)> + '_ {
+        self.cells.live_coords()
+    }
+
+    // Generation of the engine when the snapshot was taken
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+}
+
+// Double buffered simulation over any CellStorage, `Cell` by default.
+// `cells` is the current board, `cell_cache` is the back buffer
+// the next generation is written into before the two are swapped.
+pub struct Engine<const H: usize, const W: usize, C = Cell> {
+    pub(crate) cells: Box<CellArray<H, W, C>>,
+    pub(crate) cell_cache: Box<CellArray<H, W, C>>,
+    pub(crate) generation: usize,
+    previous_population: usize,
+    transitions: TransitionBreakdown,
+    drip: Option<Drip>,
+    start: Option<Box<CellArray<H, W, C>>>,
+    population_history: VecDeque<usize>,
+    history_capacity: usize,
+    // Generations every cell has been alive for, row by row, 0 for dead cells
+    ages: Vec<u16>,
+    // Generations every cell has been alive after, row by row, see visit_count
+    visits: Vec<u32>,
+    rule: Rule,
+    // Boards before the last generations with their generation, newest at the back, for undo
+    undo_boards: VecDeque<(Box<CellArray<H, W, C>>, usize)>,
+    // Boards taken back by undo with their generation, newest at the back
+    redo_boards: Vec<(Box<CellArray<H, W, C>>, usize)>,
+    undo_capacity: usize,
+    // Scratch space for generate_sparse, whether each row is active
+    active_rows: Vec<bool>,
+}
+
+// Shared handle to an engine, used by the Display to draw and advance it
+pub type EngineRef<'a, const H: usize, const W: usize> = &'a RefCell<Engine<H, W>>;
+
+impl<const H: usize, const W: usize, C: CellStorage> Engine<H, W, C> {
     pub fn new() -> Self {
         Self {
-            cells: CellArray::new(),
-            cell_cache: CellArray::new(),
+            cells: Box::new(CellArray::new()),
+            cell_cache: Box::new(CellArray::new()),
+            generation: 0,
+            previous_population: 0,
+            transitions: TransitionBreakdown::default(),
+            drip: None,
+            start: None,
+            population_history: VecDeque::new(),
+            history_capacity: HISTORY_CAPACITY,
+            ages: vec![0; H * W],
+            visits: vec![0; H * W],
+            rule: Rule::default(),
+            undo_boards: VecDeque::new(),
+            redo_boards: Vec::new(),
+            undo_capacity: 0,
+            active_rows: Vec::with_capacity(H),
+        }
+    }
+
+    // Empty engine that evolves according to `rule` instead of B3/S23
+    pub fn with_rule(rule: Rule) -> Self {
+        Self {
+            rule,
+            ..Self::new()
+        }
+    }
+
+    // Empty engine whose board handles its edges according to `topology`
+    // instead of wrapping around
+    pub fn with_topology(topology: Topology) -> Self {
+        Self::from_cells(CellArray::with_topology(topology))
+    }
+
+    // Engine at generation 0 that starts from an already built board,
+    // keeping its topology and neighbourhood
+    pub fn from_cells(cells: CellArray<H, W, C>) -> Self {
+        let cells = Box::new(cells);
+        Self {
+            cell_cache: cells.clone(),
+            cells,
+            ..Self::new()
+        }
+    }
+
+    pub fn topology(&self) -> Topology {
+        self.cells.topology()
+    }
+
+    pub fn rule(&self) -> &Rule {
+        &self.rule
+    }
+
+    // Evolve by `rule` from the next generation on. The neighbour counts
+    // do not depend on the rule, only dying cells are cleared, as their
+    // countdown belongs to the old rule.
+    pub fn set_rule(&mut self, rule: Rule) {
+        if rule == self.rule {
+            return;
+        }
+        self.rule = rule;
+
+        for y in 0..H as isize {
+            for x in 0..W as isize {
+                if self.cells.cell(x, y).dying() > 0 {
+                    self.cells.mut_cell(x, y).set_dying(0);
+                }
+            }
         }
     }
 
     pub fn randomize(&mut self) {
-        for x in 0..H {
-            for y in 0..W {
-                if rand::random() {
+        self.randomize_seeded(rand::random(), 0.5);
+    }
+
+    // Reproducible randomize, each cell is spawned with probability `density`.
+    // Panics unless `density` is between 0 and 1.
+    pub fn randomize_seeded(&mut self, seed: u64, density: f64) {
+        self.randomize_with_rng(&mut StdRng::seed_from_u64(seed), density);
+    }
+
+    // Same as randomize_seeded but draws from the given RNG,
+    // so a single seeded RNG can be threaded through a whole experiment
+    pub fn randomize_with_rng(&mut self, rng: &mut impl Rng, density: f64) {
+        assert!(
+            (0.0..=1.0).contains(&density),
+            "density must be between 0 and 1, got {}",
+            density
+        );
+        for y in 0..H {
+            for x in 0..W {
+                if rng.gen_bool(density) {
                     self.cells.spawn(x as isize, y as isize);
                 }
             }
         }
+        self.mark_start();
     }
 
-    pub fn generate(&mut self) {
-        //TODO: Optimize this
-        self.cell_cache.clone_from(&self.cells);
+    // Randomly fill one half (or quadrant) of the board with density 0.5
+    // and mirror it onto the rest. For odd dimensions the middle row or column
+    // is its own mirror image.
+    pub fn randomize_symmetric(&mut self, seed: u64, axis: Symmetry) {
+        self.randomize_symmetric_with_rng(&mut StdRng::seed_from_u64(seed), axis);
+    }
 
-        for x in 0..self.cells.rows() {
-            for y in 0..self.cells.cols() {
-                let cell = self.cell_cache.mut_cell(x as isize, y as isize);
+    pub fn randomize_symmetric_with_rng(&mut self, rng: &mut impl Rng, axis: Symmetry) {
+        let mirror_x = matches!(axis, Symmetry::Horizontal | Symmetry::Both);
+        let mirror_y = matches!(axis, Symmetry::Vertical | Symmetry::Both);
+        let half_w = if mirror_x { W.div_ceil(2) } else { W };
+        let half_h = if mirror_y { H.div_ceil(2) } else { H };
 
-                if *cell == 0b00000000 {
+        for y in 0..half_h {
+            for x in 0..half_w {
+                if !rng.gen_bool(0.5) {
                     continue;
                 }
 
-                let neighbour_count = cell.neighbours();
+                let (mx, my) = (W - 1 - x, H - 1 - y);
+                self.cells.spawn(x as isize, y as isize);
+                if mirror_x {
+                    self.cells.spawn(mx as isize, y as isize);
+                }
+                if mirror_y {
+                    self.cells.spawn(x as isize, my as isize);
+                }
+                if mirror_x && mirror_y {
+                    self.cells.spawn(mx as isize, my as isize);
+                }
+            }
+        }
+        self.mark_start();
+    }
+
+    // Remember the current board as the one restart() returns to.
+    // Randomizing does this automatically, call it after spawning by hand.
+    pub fn mark_start(&mut self) {
+        self.start = Some(self.cells.clone());
+    }
+
+    // Return to the board saved by mark_start, or to an empty board
+    // if none was saved, and start counting generations from 0 again.
+    // A drip seeding in progress is stopped, see reset_run.
+    pub fn restart(&mut self) {
+        match &self.start {
+            Some(start) => *self.cells = **start,
+            None => self.cells.reset(),
+        }
+        self.reset_run();
+    }
 
-                if cell.alive() {
-                    if neighbour_count < 2 || neighbour_count > 3 {
-                        self.cells.kill_cell(x as isize, y as isize);
-                    }
+    // Spawn every (x, y) on the current board, wrapping around the edges
+    pub fn spawn_cells(&mut self, coords: &[(isize, isize)]) {
+        for &(x, y) in coords {
+            self.cells.spawn(x, y);
+        }
+    }
+
+    // Spawn the pattern in the middle of the current board, see CellArray::stamp_centered
+    pub fn spawn_centered(&mut self, pattern: &[(isize, isize)]) {
+        self.cells.stamp_centered(pattern);
+    }
+
+    // Kill every (x, y) on the current board, wrapping around the edges.
+    // Their ages start over, so a cell spawned there later is newborn.
+    pub fn kill_cells(&mut self, coords: &[(isize, isize)]) {
+        for &(x, y) in coords {
+            self.cells.kill_cell(x, y);
+            if let Some((x, y)) = self.cells.resolve(x, y) {
+                self.ages[y * W + x] = 0;
+            }
+        }
+    }
+
+    // Spawn the cell at (x, y) if it is dead, kill it otherwise, see kill_cells
+    pub fn toggle_cell(&mut self, x: isize, y: isize) {
+        if self.cells.cell(x, y).is_alive() {
+            self.kill_cells(&[(x, y)]);
+        } else {
+            self.cells.spawn(x, y);
+        }
+    }
+
+    // Kill every cell and start counting generations from 0 again.
+    // The board saved by mark_start is kept, so restart() still returns to it.
+    pub fn clear(&mut self) {
+        self.cells.reset();
+        self.reset_run();
+    }
+
+    // Forget everything recorded about the run so far after the board was replaced:
+    // generation, population history, transitions, ages, undo and redo, and
+    // any drip seeding. The back buffer matches the board, so nothing counts as changed.
+    pub(crate) fn reset_run(&mut self) {
+        *self.cell_cache = *self.cells;
+        self.generation = 0;
+        // Loaders may go on from a saved generation, where the delta has to be 0 too
+        self.previous_population = self.cells.population();
+        self.transitions = TransitionBreakdown::default();
+        self.population_history.clear();
+        self.ages.fill(0);
+        self.undo_boards.clear();
+        self.redo_boards.clear();
+        self.drip = None;
+    }
+
+    // Compute the next generation into the back buffer and swap it to the front.
+    // The back buffer only holds the generation before last, so it is cleared
+    // and rebuilt rather than copied from the front buffer.
+    pub fn generate(&mut self) {
+        self.generate_rows(|_| true);
+    }
+
+    // Same result as generate, but rows without alive cells in or next to them
+    // are skipped. Much faster on large, mostly dead boards.
+    // Dying cells are not tracked per row, so rules with more than 2 states visit every row.
+    pub fn generate_sparse(&mut self) {
+        if self.rule.states() > 2 {
+            return self.generate();
+        }
+        let mut active = std::mem::take(&mut self.active_rows);
+        active.clear();
+        active.extend((0..H).map(|y| self.cells.row_is_active(y)));
+        self.generate_rows(|y| active[y]);
+        self.active_rows = active;
+    }
+
+    fn generate_rows(&mut self, active: impl Fn(usize) -> bool) {
+        self.redo_boards.clear();
+        if self.undo_capacity > 0 {
+            if self.undo_boards.len() == self.undo_capacity {
+                self.undo_boards.pop_front();
+            }
+            self.undo_boards
+                .push_back((self.cells.clone(), self.generation));
+        }
+
+        self.previous_population = self.cells.population();
+        self.transitions = TransitionBreakdown::default();
+        self.cell_cache.reset();
+
+        let transitions = &mut self.transitions;
+        self.cells.step_rows_into(
+            &mut self.cell_cache,
+            &self.rule,
+            active,
+            |neighbours, born| {
+                let (births, deaths) = &mut transitions[neighbours as usize];
+                if born {
+                    *births += 1;
                 } else {
-                    if neighbour_count == 3 {
-                        self.cells.spawn(x as isize, y as isize);
-                    }
+                    *deaths += 1;
+                }
+            },
+        );
+
+        std::mem::swap(&mut self.cells, &mut self.cell_cache);
+        self.generation += 1;
+        self.record_population();
+        self.update_ages();
+        self.update_visits();
+    }
+
+    // Survivors age by one generation, newborn and dead cells start over at 0.
+    // Rows that are empty before and after keep their ages, which are all 0 already.
+    fn update_ages(&mut self) {
+        for y in 0..H {
+            if !self.cells.row_is_active(y) && !self.cell_cache.row_is_active(y) {
+                continue;
+            }
+            for x in 0..W {
+                let (ix, iy) = (x as isize, y as isize);
+                let age = &mut self.ages[y * W + x];
+                *age = match (
+                    self.cell_cache.cell(ix, iy).is_alive(),
+                    self.cells.cell(ix, iy).is_alive(),
+                ) {
+                    (true, true) => age.saturating_add(1),
+                    _ => 0,
+                };
+            }
+        }
+    }
+
+    fn update_visits(&mut self) {
+        for y in 0..H {
+            if !self.cells.row_is_active(y) {
+                continue;
+            }
+            for x in 0..W {
+                if self.cells.cell(x as isize, y as isize).is_alive() {
+                    self.visits[y * W + x] += 1;
                 }
             }
         }
     }
 
-    pub fn cells(&self) -> &CellArray<H, W> {
+    // Advance one generation, same as generate()
+    pub fn step(&mut self) {
+        self.generate();
+    }
+
+    // Advance n generations without anything being drawn in between.
+    // Both boards are reused for the whole run and rows without
+    // any life near them are skipped, so fast-forwarding is cheap.
+    pub fn step_n(&mut self, n: usize) {
+        for _ in 0..n {
+            self.generate_sparse();
+        }
+    }
+
+    // Push the new population to the history, the first call
+    // also records the population the run started with
+    fn record_population(&mut self) {
+        if self.population_history.is_empty() {
+            self.population_history.push_back(self.previous_population);
+        }
+        self.population_history.push_back(self.cells.population());
+
+        while self.population_history.len() > self.history_capacity {
+            self.population_history.pop_front();
+        }
+    }
+
+    pub fn cells(&self) -> &CellArray<H, W, C> {
         &self.cells
     }
 
-}
+    // Copy of the current board that is not affected by later generations
+    pub fn snapshot(&self) -> BoardSnapshot<H, W, C> {
+        BoardSnapshot {
+            cells: self.cells.clone(),
+            generation: self.generation,
+        }
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::{
-        thread,
-        time::{Duration, Instant},
-    };
+    // Endless iterator that generates and yields a snapshot of every new board,
+    // e.g. engine.generations().take(100). The engine is consumed,
+    // see generations_mut to keep using it afterwards.
+    pub fn generations(mut self) -> impl Iterator<Item = BoardSnapshot<H, W, C>> {
+        std::iter::repeat_with(move || self.next_snapshot())
+    }
 
-    fn measure_execution_time<F>(mut f: F, count: usize) -> (Duration, Duration)
-    where
-        F: FnMut(),
-    {
-        let mut total_time = Duration::new(0, 0);
+    // Same as generations, borrowing the engine. It is left at the last
+    // generation that was yielded.
+    pub fn generations_mut(&mut self) -> impl Iterator<Item = BoardSnapshot<H, W, C>> + '_ {
+        std::iter::repeat_with(move || self.next_snapshot())
+    }
 
-        for _ in 0..count {
-            let start = Instant::now();
-            f();
-            let duration = start.elapsed();
-            total_time += duration;
+    // One step of generations and generations_mut
+    fn next_snapshot(&mut self) -> BoardSnapshot<H, W, C> {
+        self.generate();
+        self.snapshot()
+    }
+
+    // Generations the cell at (x, y) has survived since it was born
+    // or placed on the board, 0 for dead cells
+    pub fn cell_age(&self, x: isize, y: isize) -> u16 {
+        match self.cells.resolve(x, y) {
+            Some((x, y)) if self.cells.cell(x as isize, y as isize).is_alive() => {
+                self.ages[y * W + x]
+            }
+            _ => 0,
         }
+    }
 
-        let average_time = total_time / count as u32;
-        (average_time, total_time)
+    // Generations after which the cell at (x, y) was alive, counted since
+    // the engine was created or reset_heatmap was last called.
+    // Restarting or clearing the board keeps the counts.
+    pub fn visit_count(&self, x: isize, y: isize) -> u32 {
+        match self.cells.resolve(x, y) {
+            Some((x, y)) => self.visits[y * W + x],
+            None => 0,
+        }
+    }
+
+    // Every visit_count, row by row
+    pub fn visit_counts(&self) -> &[u32] {
+        &self.visits
+    }
+
+    pub fn reset_heatmap(&mut self) {
+        self.visits.fill(0);
+    }
+
+    // Number of generations computed so far
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    // Number of alive cells on the current board
+    pub fn population(&self) -> usize {
+        self.cells.population()
+    }
+
+    // True once no cell is alive
+    pub fn is_extinct(&self) -> bool {
+        self.population() == 0
+    }
+
+    // Whether the cell at (x, y) will be alive in the next generation, from its
+    // stored neighbour count and the rule. The board itself does not change.
+    pub fn next_cell_state(&self, x: isize, y: isize) -> bool {
+        let cell = self.cells.cell(x, y);
+        if cell.dying() > 0 {
+            false
+        } else if cell.is_alive() {
+            self.rule.survives(cell.neighbours())
+        } else {
+            self.rule.born(cell.neighbours())
+        }
+    }
+
+    // Whether the cells from (x0, y0) to (x1, y1) inclusive stay the same
+    // in the next generation, e.g. an eater that is done absorbing a glider.
+    // The engine itself does not advance. Cells outside the rectangle still
+    // take part, so a region is only stable as long as its surroundings let it.
+    pub fn is_still_life_region(&self, x0: isize, y0: isize, x1: isize, y1: isize) -> bool {
+        let mut next = self.cells.clone();
+        next.reset();
+        self.cells.step_into(&mut next, &self.rule, |_, _| {});
+
+        (y0..=y1).all(|y| {
+            (x0..=x1).all(|x| self.cells.cell(x, y).is_alive() == next.cell(x, y).is_alive())
+        })
+    }
+
+    // Generate until the board is empty, at most `max_gens` generations.
+    // Returns the generation at which it died out, which is the current one
+    // if it already was empty, or None if something is still alive.
+    pub fn generate_until_extinct(&mut self, max_gens: usize) -> Option<usize> {
+        for _ in 0..max_gens {
+            if self.is_extinct() {
+                break;
+            }
+            self.generate();
+        }
+        self.is_extinct().then_some(self.generation)
+    }
+
+    // (births, deaths) during the last generate(), indexed by the neighbour count
+    // the cell had when the rule was applied
+    pub fn transition_breakdown(&self) -> TransitionBreakdown {
+        self.transitions
+    }
+
+    // Change in population caused by the last generate(), 0 before the first one
+    pub fn population_delta(&self) -> i64 {
+        if self.generation == 0 {
+            return 0;
+        }
+        self.cells.population() as i64 - self.previous_population as i64
+    }
+
+    // Populations of the most recent generations, oldest first.
+    // Holds at most history_capacity entries and is empty before the first generate().
+    pub fn history(&self) -> &VecDeque<usize> {
+        &self.population_history
+    }
+
+    pub fn history_capacity(&self) -> usize {
+        self.history_capacity
+    }
+
+    // Change how many populations are kept, dropping the oldest ones if needed
+    pub fn set_history_capacity(&mut self, capacity: usize) {
+        self.history_capacity = capacity;
+        while self.population_history.len() > capacity {
+            self.population_history.pop_front();
+        }
+    }
+
+    // How many generations undo can take back, 0 (the default) turns undo off.
+    // Every step keeps a full copy of the board, so large boards want a small depth.
+    pub fn set_undo_capacity(&mut self, capacity: usize) {
+        self.undo_capacity = capacity;
+        while self.undo_boards.len() > capacity {
+            self.undo_boards.pop_front();
+        }
+    }
+
+    pub fn undo_capacity(&self) -> usize {
+        self.undo_capacity
+    }
+
+    // Go back to the board before the last generate, false if there is none left.
+    // The generation is the one the board was saved at. The population history
+    // follows, the transition counts do not. Cells alive on both boards keep
+    // their age, the others start over.
+    pub fn undo(&mut self) -> bool {
+        let Some((board, generation)) = self.undo_boards.pop_back() else {
+            return false;
+        };
+        let current = std::mem::replace(&mut self.cells, board);
+        self.redo_boards.push((current, self.generation));
+        self.generation = generation;
+        self.population_history.pop_back();
+        self.sync_previous_board();
+        self.clear_dead_ages();
+        true
+    }
+
+    // Take back the last undo, false if there is nothing to redo.
+    // Generating or restarting in between drops everything that could be redone.
+    pub fn redo(&mut self) -> bool {
+        let Some((board, generation)) = self.redo_boards.pop() else {
+            return false;
+        };
+        let current = std::mem::replace(&mut self.cells, board);
+        self.undo_boards.push_back((current, self.generation));
+        self.generation = generation;
+        self.population_history.push_back(self.cells.population());
+        self.sync_previous_board();
+        self.clear_dead_ages();
+        true
+    }
+
+    // Point the back buffer at the board before the current one after undo or redo,
+    // so changed_since_last_generation (and the Display drawing only the changes)
+    // and population_delta compare against the right board. Without a saved board
+    // there is nothing to compare against and no cell counts as changed.
+    fn sync_previous_board(&mut self) {
+        let previous = self
+            .undo_boards
+            .back()
+            .map_or(&self.cells, |(board, _)| board);
+        *self.cell_cache = **previous;
+        self.previous_population = self.cell_cache.population();
+    }
+
+    // Dead cells have age 0, so cells a board swap brought back are newborn
+    fn clear_dead_ages(&mut self) {
+        for (y, row) in self.cells.rows_iter().enumerate() {
+            for (x, cell) in row.iter().enumerate() {
+                if !cell.is_alive() {
+                    self.ages[y * W + x] = 0;
+                }
+            }
+        }
+    }
+
+    // Highest population in the history, the current one if the history is empty
+    pub fn peak_population(&self) -> usize {
+        self.population_history
+            .iter()
+            .copied()
+            .max()
+            .unwrap_or_else(|| self.population())
+    }
+
+    // Average population in the history, the current one if the history is empty
+    pub fn mean_population(&self) -> f64 {
+        if self.population_history.is_empty() {
+            return self.population() as f64;
+        }
+        self.population_history.iter().sum::<usize>() as f64 / self.population_history.len() as f64
+    }
+
+    // (x, y) of every cell whose alive state differs from the board before
+    // the last generate(), row by row. Empty before the first generation.
+    pub fn changed_since_last_generation(&self) -> Vec<(usize, usize)> {
+        if self.generation == 0 {
+            return Vec::new();
+        }
+
+        // After the swap the back buffer holds the previous board
+        let mut changed = Vec::new();
+        for y in 0..H {
+            for x in 0..W {
+                let before = self.cell_cache.cell(x as isize, y as isize).is_alive();
+                if self.cells.cell(x as isize, y as isize).is_alive() != before {
+                    changed.push((x, y));
+                }
+            }
+        }
+        changed
+    }
+
+    // Advance one generation and summarize the result, for driving the engine
+    // from an external event loop or timer instead of Display::run.
+    // Never sleeps, the caller decides when the next tick happens.
+    pub fn tick(&mut self) -> TickSummary {
+        self.generate_sparse();
+
+        TickSummary {
+            generation: self.generation,
+            population: self.population(),
+            stable: self.changed_since_last_generation().is_empty(),
+            extinct: self.is_extinct(),
+        }
+    }
+
+    // Generate and report which cells were born and which died
+    pub fn generate_with_events(&mut self) -> GenerationEvents {
+        self.generate();
+
+        let (born, died) = self
+            .changed_since_last_generation()
+            .into_iter()
+            .partition(|&(x, y)| self.cells.cell(x as isize, y as isize).is_alive());
+        GenerationEvents { born, died }
+    }
+
+    // Prepare to spawn `total` random cells, `cells_per_gen` at a time,
+    // over the following calls to generate_dripping
+    pub fn drip_seed(&mut self, cells_per_gen: usize, total: usize, seed: u64) {
+        self.drip_seed_with_rng(cells_per_gen, total, &mut StdRng::seed_from_u64(seed));
+    }
+
+    // Same as drip_seed but seeded from the given RNG. The drip keeps its own RNG,
+    // so `rng` only advances once here, not with every dripped cell.
+    pub fn drip_seed_with_rng(&mut self, cells_per_gen: usize, total: usize, rng: &mut impl Rng) {
+        self.drip = Some(Drip {
+            rng: StdRng::seed_from_u64(rng.gen()),
+            cells_per_gen,
+            remaining: total,
+            injected: 0,
+        });
+    }
+
+    // Compute the next generation, then spawn the next batch of drip cells.
+    // Cells are only dropped onto dead positions so every one is a new spawn.
+    pub fn generate_dripping(&mut self) {
+        self.generate();
+
+        let Some(drip) = self.drip.as_mut() else {
+            return;
+        };
+
+        let batch = drip.cells_per_gen.min(drip.remaining);
+        for _ in 0..batch {
+            if self.cells.population() == H * W {
+                break;
+            }
+
+            loop {
+                let x = drip.rng.gen_range(0..W) as isize;
+                let y = drip.rng.gen_range(0..H) as isize;
+                if !self.cells.cell(x, y).is_alive() {
+                    self.cells.spawn(x, y);
+                    break;
+                }
+            }
+
+            drip.remaining -= 1;
+            drip.injected += 1;
+        }
+    }
+
+    // Number of cells spawned by the drip so far
+    pub fn dripped(&self) -> usize {
+        self.drip.as_ref().map_or(0, |drip| drip.injected)
+    }
+
+    // Compact text form of the board: '#' for alive, '.' for dead, one row per line
+    pub fn render_ascii(&self) -> String {
+        let mut ascii = String::with_capacity((W + 1) * H);
+        for row in self.cells.rows_iter() {
+            ascii.extend(
+                row.iter()
+                    .map(|cell| if cell.is_alive() { '#' } else { '.' }),
+            );
+            ascii.push('\n');
+        }
+        ascii
+    }
+
+    // Generate until the board repeats one of the last `max_period` boards
+    // or until `max_gens` generations have been computed.
+    // Boards are compared by their fingerprint.
+    pub fn generate_until_stable(&mut self, max_gens: usize, max_period: usize) -> StabilityResult {
+        let mut history: VecDeque<u64> = VecDeque::with_capacity(max_period + 1);
+        history.push_back(self.cells.fingerprint());
+
+        for _ in 0..max_gens {
+            self.generate();
+            let hash = self.cells.fingerprint();
+
+            if let Some(period) = history.iter().rev().position(|&past| past == hash) {
+                return StabilityResult {
+                    stabilized: true,
+                    period: period + 1,
+                    generation: self.generation,
+                };
+            }
+
+            history.push_back(hash);
+            if history.len() > max_period {
+                history.pop_front();
+            }
+        }
+
+        StabilityResult {
+            stabilized: false,
+            period: 0,
+            generation: self.generation,
+        }
+    }
+
+    // Run `trials` random soups of density 0.5, seeded with seed_base, seed_base + 1, ...,
+    // for at most `gens` generations each. The best soup is the one that lives the
+    // longest before it settles, ties go to the larger final population, then the
+    // earlier seed. None if there are no trials.
+    pub fn search_soups(trials: usize, gens: usize, seed_base: u64) -> Option<SoupResult> {
+        let trials: Vec<SoupTrial> = (0..trials as u64)
+            .map(|trial| {
+                let seed = seed_base.wrapping_add(trial);
+                let mut engine = Self::new();
+                engine.randomize_seeded(seed, 0.5);
+                let stability = engine.generate_until_stable(gens, SOUP_MAX_PERIOD);
+
+                SoupTrial {
+                    seed,
+                    population: engine.population(),
+                    stabilized: stability.stabilized,
+                    lifespan: stability.generation,
+                }
+            })
+            .collect();
+
+        let best = *trials
+            .iter()
+            .rev()
+            .max_by_key(|trial| (trial.lifespan, trial.population))?;
+        Some(SoupResult { best, trials })
+    }
+
+    // Count the blocks, beehives, loaves and blinkers on the board, e.g. once
+    // generate_until_stable is done. Every 8-connected cluster of alive cells
+    // (see CellArray::clusters) is matched in any rotation or reflection.
+    // Objects close enough to touch form one cluster and end up as unknown.
+    pub fn classify_objects(&self) -> ObjectCensus {
+        let known = [
+            Block.cells(),
+            Beehive.cells(),
+            Loaf.cells(),
+            Blinker.cells(),
+        ]
+        .map(canonical_form_of);
+
+        let mut census = ObjectCensus::default();
+        for cluster in self.cells.clusters() {
+            let shape = canonical_form_of(&cluster);
+            let count = match known.iter().position(|form| *form == shape) {
+                Some(0) => &mut census.blocks,
+                Some(1) => &mut census.beehives,
+                Some(2) => &mut census.loaves,
+                Some(3) => &mut census.blinkers,
+                _ => &mut census.unknown,
+            };
+            *count += 1;
+        }
+        census
+    }
+
+    // Run the given number of generations and count the spaceships leaving
+    // the region (x0, y0, x1, y1), e.g. the output of a glider gun.
+    // Every generation the glider sized clusters lying entirely outside the
+    // region are counted, and any increase over the previous count is an emission.
+    pub fn count_emissions(
+        &mut self,
+        region: (isize, isize, isize, isize),
+        generations: usize,
+    ) -> usize {
+        let (x0, y0, x1, y1) = region;
+        let outside = |cells: &CellArray<H, W, C>| {
+            cells
+                .clusters()
+                .iter()
+                .filter(|cluster| is_glider_sized(cluster))
+                .filter(|cluster| {
+                    cluster.iter().all(|&(x, y)| {
+                        let (x, y) = cells.resolve(x, y).expect("clusters lie on the board");
+                        let (x, y) = (x as isize, y as isize);
+                        x < x0 || x > x1 || y < y0 || y > y1
+                    })
+                })
+                .count()
+        };
+
+        let mut emissions = 0;
+        let mut previous = outside(&self.cells);
+        for _ in 0..generations {
+            self.generate();
+            let current = outside(&self.cells);
+            emissions += current.saturating_sub(previous);
+            previous = current;
+        }
+
+        emissions
+    }
+
+    // Run the given number of generations and return every coordinate
+    // that was alive at any point, including the starting board
+    pub fn run_collecting_trail(&mut self, generations: usize) -> HashSet<(isize, isize)> {
+        let mut trail: HashSet<(isize, isize)> = self.cells.live_coords().collect();
+
+        for _ in 0..generations {
+            self.generate();
+            trail.extend(self.cells.live_coords());
+        }
+
+        trail
+    }
+
+    // Run the given number of generations and count for every cell how often
+    // it changed state. The counts are row-major, `y * W + x`.
+    pub fn run_collecting_heat(&mut self, generations: usize) -> Vec<u32> {
+        let mut heat = vec![0; H * W];
+
+        for _ in 0..generations {
+            self.generate();
+            for (x, y) in self.changed_since_last_generation() {
+                heat[y * W + x] += 1;
+            }
+        }
+
+        heat
+    }
+}
+
+impl<const H: usize, const W: usize, C: CellStorage> Default for Engine<H, W, C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Every phase of a glider is 5 cells inside a 3x3 box
+fn is_glider_sized(cluster: &[(isize, isize)]) -> bool {
+    if cluster.len() != 5 {
+        return false;
+    }
+
+    let width = cluster.iter().map(|&(x, _)| x).max().unwrap()
+        - cluster.iter().map(|&(x, _)| x).min().unwrap();
+    let height = cluster.iter().map(|&(_, y)| y).max().unwrap()
+        - cluster.iter().map(|&(_, y)| y).min().unwrap();
+    width == 2 && height == 2
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gol::patterns::{
+        spawn_blinker, spawn_glider, spawn_glider_gun, stamp, stamp_oriented, Flip, Glider,
+        Rotation,
+    };
+    use std::{
+        thread,
+        time::{Duration, Instant},
+    };
+
+    fn measure_execution_time<F>(mut f: F, count: usize) -> (Duration, Duration)
+    where
+        F: FnMut(),
+    {
+        let mut total_time = Duration::new(0, 0);
+
+        for _ in 0..count {
+            let start = Instant::now();
+            f();
+            let duration = start.elapsed();
+            total_time += duration;
+        }
+
+        let average_time = total_time / count as u32;
+        (average_time, total_time)
+    }
+
+    #[test]
+    fn test_generation_count() {
+        const GENERATIONS: usize = 10;
+        let mut engine = Engine::<20, 20>::new();
+        engine.randomize();
+        assert_eq!(engine.generation(), 0);
+
+        for _ in 0..GENERATIONS {
+            engine.generate();
+        }
+        assert_eq!(engine.generation(), GENERATIONS);
+    }
+
+    #[test]
+    fn test_population_matches_scan() {
+        let mut engine = Engine::<20, 20>::new();
+        engine.randomize();
+
+        for _ in 0..10 {
+            let cells = engine.cells();
+            let mut scanned = 0;
+            for y in 0..cells.rows() {
+                for x in 0..cells.cols() {
+                    if cells.cell(x as isize, y as isize).alive() {
+                        scanned += 1;
+                    }
+                }
+            }
+            assert_eq!(engine.population(), scanned);
+            engine.generate();
+        }
+    }
+
+    #[test]
+    fn test_blinker_trail() {
+        let mut engine = Engine::<5, 5>::new();
+        for y in 1..4 {
+            engine.cells.spawn(2, y);
+        }
+
+        let trail = engine.run_collecting_trail(6);
+        let expected: HashSet<(isize, isize)> = [(2, 1), (2, 2), (2, 3), (1, 2), (3, 2)]
+            .into_iter()
+            .collect();
+        assert_eq!(trail, expected);
+    }
+
+    #[test]
+    fn test_changed_since_last_generation() {
+        let mut engine = Engine::<5, 5>::new();
+        engine.spawn_cells(&[(1, 2), (2, 2), (3, 2)]);
+        assert!(engine.changed_since_last_generation().is_empty());
+
+        engine.generate();
+        assert_eq!(
+            engine.changed_since_last_generation(),
+            vec![(2, 1), (1, 2), (3, 2), (2, 3)]
+        );
+        engine.generate();
+        assert_eq!(
+            engine.changed_since_last_generation(),
+            vec![(2, 1), (1, 2), (3, 2), (2, 3)]
+        );
+
+        let mut block = Engine::<5, 5>::new();
+        block.spawn_cells(&[(1, 1), (2, 1), (1, 2), (2, 2)]);
+        block.generate();
+        assert!(block.changed_since_last_generation().is_empty());
+    }
+
+    #[test]
+    fn test_blinker_heat() {
+        let mut engine = Engine::<5, 5>::new();
+        for y in 1..4 {
+            engine.cells.spawn(2, y);
+        }
+
+        let heat = engine.run_collecting_heat(6);
+        assert_eq!(heat.len(), 25);
+        for y in 0..5 {
+            for x in 0..5 {
+                let expected = match (x, y) {
+                    // The ends of both phases flip every generation
+                    (2, 1) | (2, 3) | (1, 2) | (3, 2) => 6,
+                    _ => 0,
+                };
+                assert_eq!(heat[y * 5 + x], expected, "cell ({}, {})", x, y);
+            }
+        }
+    }
+
+    #[test]
+    fn test_population_history() {
+        // A diagonal of three cells shrinks to one and then dies out
+        let mut engine = Engine::<8, 8>::new();
+        for i in 0..3 {
+            engine.cells.spawn(i, i);
+        }
+        assert!(engine.history().is_empty());
+        assert_eq!(engine.peak_population(), 3);
+
+        for _ in 0..5 {
+            engine.generate();
+        }
+
+        let history: Vec<usize> = engine.history().iter().copied().collect();
+        assert_eq!(history, vec![3, 1, 0, 0, 0, 0]);
+        assert!(history.windows(2).all(|pair| pair[0] >= pair[1]));
+        assert_eq!(engine.peak_population(), 3);
+        assert!((engine.mean_population() - 4.0 / 6.0).abs() < 1e-9);
+
+        // Only the most recent populations are kept
+        engine.set_history_capacity(2);
+        assert_eq!(engine.history().len(), 2);
+        assert_eq!(engine.peak_population(), 0);
+        engine.generate();
+        assert_eq!(engine.history().len(), 2);
+    }
+
+    #[test]
+    fn test_blinker_stabilizes_with_period_2() {
+        let mut engine = Engine::<6, 6>::new();
+        for y in 1..4 {
+            engine.cells.spawn(2, y);
+        }
+
+        let result = engine.generate_until_stable(100, 4);
+        assert!(result.stabilized);
+        assert_eq!(result.period, 2);
+        assert_eq!(result.generation, 2);
+    }
+
+    #[test]
+    fn test_block_stabilizes_with_period_1() {
+        let mut engine = Engine::<6, 6>::new();
+        for &(x, y) in &[(1, 1), (2, 1), (1, 2), (2, 2)] {
+            engine.cells.spawn(x, y);
+        }
+
+        let result = engine.generate_until_stable(100, 4);
+        assert!(result.stabilized);
+        assert_eq!(result.period, 1);
+        assert_eq!(result.generation, 1);
+    }
+
+    #[test]
+    fn test_dying_cells_count_for_stability() {
+        // A lone cell is dying at generation 1 and only gone for good at generation 2
+        let mut engine = Engine::<6, 6>::with_rule(Rule::brians_brain());
+        engine.cells.spawn(2, 2);
+
+        let result = engine.generate_until_stable(100, 4);
+        assert!(result.stabilized);
+        assert_eq!(result.period, 1);
+        assert_eq!(result.generation, 3);
+    }
+
+    #[test]
+    fn test_unstable_within_limit() {
+        // A glider on a large board needs far more than 3 generations to repeat
+        let mut engine = Engine::<20, 20>::new();
+        for &(x, y) in &[(2, 0), (2, 1), (2, 2), (1, 2), (0, 1)] {
+            engine.cells.spawn(x, y);
+        }
+
+        let result = engine.generate_until_stable(3, 4);
+        assert!(!result.stabilized);
+        assert_eq!(result.period, 0);
+        assert_eq!(result.generation, 3);
+    }
+
+    #[test]
+    fn test_randomize_with_rng_is_reproducible() {
+        let mut rng_a = StdRng::seed_from_u64(7);
+        let mut rng_b = rng_a.clone();
+
+        // Two successive randomizes drawing from the same RNG stream
+        let mut first_a = Engine::<20, 20>::new();
+        let mut second_a = Engine::<20, 20>::new();
+        first_a.randomize_with_rng(&mut rng_a, 0.5);
+        second_a.randomize_with_rng(&mut rng_a, 0.5);
+
+        let mut first_b = Engine::<20, 20>::new();
+        let mut second_b = Engine::<20, 20>::new();
+        first_b.randomize_with_rng(&mut rng_b, 0.5);
+        second_b.randomize_with_rng(&mut rng_b, 0.5);
+
+        assert_eq!(first_a.cells().fingerprint(), first_b.cells().fingerprint());
+        assert_eq!(
+            second_a.cells().fingerprint(),
+            second_b.cells().fingerprint()
+        );
+        assert_ne!(
+            first_a.cells().fingerprint(),
+            second_a.cells().fingerprint()
+        );
+
+        // Drip seeding threads the RNG the same way
+        let mut drip_a = Engine::<20, 20>::new();
+        let mut drip_b = Engine::<20, 20>::new();
+        drip_a.drip_seed_with_rng(4, 12, &mut rng_a);
+        drip_b.drip_seed_with_rng(4, 12, &mut rng_b);
+        for _ in 0..3 {
+            drip_a.generate_dripping();
+            drip_b.generate_dripping();
+        }
+        assert_eq!(drip_a.dripped(), 12);
+        assert_eq!(*drip_a.cells(), *drip_b.cells());
+    }
+
+    #[test]
+    #[should_panic(expected = "density must be between 0 and 1")]
+    fn test_randomize_density_out_of_range() {
+        Engine::<4, 4>::new().randomize_seeded(1, 1.5);
+    }
+
+    #[test]
+    #[should_panic(expected = "density must be between 0 and 1")]
+    fn test_randomize_density_nan() {
+        Engine::<4, 4>::new().randomize_seeded(1, f64::NAN);
+    }
+
+    #[test]
+    fn test_randomize_seeded() {
+        let mut a = Engine::<50, 50>::new();
+        let mut b = Engine::<50, 50>::new();
+        a.randomize_seeded(42, 0.5);
+        b.randomize_seeded(42, 0.5);
+        assert_eq!(a.population(), b.population());
+        assert_eq!(a.cells().fingerprint(), b.cells().fingerprint());
+
+        let mut sparse = Engine::<50, 50>::new();
+        let mut dense = Engine::<50, 50>::new();
+        sparse.randomize_seeded(42, 0.1);
+        dense.randomize_seeded(42, 0.9);
+
+        // 2500 cells: expected ~250 vs ~2250 alive
+        assert!(sparse.population() < 500);
+        assert!(dense.population() > 2000);
+    }
+
+    #[test]
+    fn test_randomize_non_square() {
+        let mut engine = Engine::<3, 7>::new();
+        engine.randomize_seeded(1, 1.0);
+        assert_eq!(engine.population(), 3 * 7);
+        assert!(engine
+            .cells()
+            .to_bool_grid()
+            .iter()
+            .flatten()
+            .all(|&alive| alive));
+    }
+
+    #[test]
+    fn test_randomize_symmetric() {
+        let mut engine = Engine::<9, 11>::new();
+        engine.randomize_symmetric(21, Symmetry::Horizontal);
+        assert!(engine.population() > 0);
+        for (x, y) in engine.cells().live_coords() {
+            assert!(engine.cells().cell(10 - x, y).alive(), "({}, {})", x, y);
+        }
+
+        let mut engine = Engine::<9, 11>::new();
+        engine.randomize_symmetric(21, Symmetry::Both);
+        for (x, y) in engine.cells().live_coords() {
+            assert!(engine.cells().cell(10 - x, y).alive());
+            assert!(engine.cells().cell(x, 8 - y).alive());
+        }
+
+        // Neighbour counts stay consistent across the mirror lines
+        let mut copy = Engine::<9, 11>::new();
+        let coords: Vec<_> = engine.cells().live_coords().collect();
+        copy.spawn_cells(&coords);
+        assert_eq!(copy.cells().fingerprint(), engine.cells().fingerprint());
+    }
+
+    #[test]
+    fn test_restart() {
+        let mut engine = Engine::<30, 30>::new();
+        engine.randomize_seeded(7, 0.4);
+        let seeded = engine.cells().fingerprint();
+
+        for _ in 0..20 {
+            engine.generate();
+        }
+        assert_ne!(engine.cells().fingerprint(), seeded);
+
+        engine.restart();
+        assert_eq!(engine.cells().fingerprint(), seeded);
+        assert_eq!(engine.generation(), 0);
+        assert_eq!(engine.population_delta(), 0);
+
+        // Cells dripped before the restart stop coming
+        engine.drip_seed(10, 100, 3);
+        engine.generate_dripping();
+        assert_eq!(engine.dripped(), 10);
+        engine.restart();
+        assert_eq!(engine.dripped(), 0);
+        let mut plain = Engine::<30, 30>::new();
+        plain.randomize_seeded(7, 0.4);
+        engine.generate_dripping();
+        plain.generate();
+        assert_eq!(*engine.cells(), *plain.cells());
+
+        // Without a start board restart clears
+        let mut empty = Engine::<5, 5>::new();
+        spawn_blinker(&mut empty.cells, 1, 2);
+        empty.generate();
+        empty.restart();
+        assert_eq!(empty.population(), 0);
+    }
+
+    #[test]
+    fn test_spawn_and_kill_cells() {
+        let blinker = [(1, 2), (2, 2), (3, 2)];
+        let mut engine = Engine::<5, 5>::new();
+        engine.spawn_cells(&blinker);
+
+        engine.generate();
+        assert_eq!(
+            engine.cells().live_coords().collect::<Vec<_>>(),
+            vec![(2, 1), (2, 2), (2, 3)]
+        );
+        engine.generate();
+        assert_eq!(engine.cells().live_coords().collect::<Vec<_>>(), blinker);
+
+        engine.kill_cells(&blinker);
+        assert_eq!(engine.population(), 0);
+        assert_eq!(engine.cells().cell(2, 1).neighbours(), 0);
+    }
+
+    #[test]
+    fn test_default() {
+        let cell = Cell::default();
+        assert!(!cell.alive());
+        assert_eq!(cell.neighbours(), 0);
+
+        let cell_array = CellArray::<4, 6>::default();
+        assert_eq!(cell_array.population(), 0);
+        assert_eq!(
+            cell_array.fingerprint(),
+            CellArray::<4, 6>::new().fingerprint()
+        );
+
+        let engine = Engine::<4, 6>::default();
+        assert_eq!(engine.population(), 0);
+        assert_eq!(engine.generation(), 0);
+        assert!(engine.cells().live_coords().next().is_none());
+    }
+
+    #[test]
+    fn test_cell_age() {
+        let mut engine = Engine::<6, 6>::new();
+        engine.spawn_cells(&[(2, 2), (3, 2), (2, 3), (3, 3)]);
+        assert_eq!(engine.cell_age(2, 2), 0);
+        for _ in 0..7 {
+            engine.generate();
+        }
+        for (x, y) in [(2, 2), (3, 2), (2, 3), (3, 3)] {
+            assert_eq!(engine.cell_age(x, y), 7);
+        }
+        assert_eq!(engine.cell_age(0, 0), 0);
+
+        // A cell placed where one was killed is newborn
+        engine.kill_cells(&[(2, 2)]);
+        engine.spawn_cells(&[(2, 2)]);
+        engine.generate();
+        assert_eq!(engine.cell_age(2, 2), 1);
+        assert_eq!(engine.cell_age(3, 3), 8);
+        engine.toggle_cell(3, 3);
+        engine.toggle_cell(3, 3);
+        assert_eq!(engine.cell_age(3, 3), 0);
+
+        // Also after an undo took the cell away, an L tromino becomes a block
+        let mut engine = Engine::<6, 6>::new();
+        engine.set_undo_capacity(4);
+        engine.spawn_cells(&[(1, 1), (2, 1), (1, 2)]);
+        engine.step_n(2);
+        assert_eq!(engine.cell_age(2, 2), 1);
+        assert!(engine.undo() && engine.undo());
+        engine.spawn_cells(&[(2, 2)]);
+        assert_eq!(engine.cell_age(2, 2), 0);
+
+        // A blinker's tips are reborn every generation, its centre survives
+        let mut engine = Engine::<6, 6>::new();
+        engine.spawn_cells(&[(1, 2), (2, 2), (3, 2)]);
+        engine.step_n(3);
+        assert_eq!(engine.cell_age(2, 2), 3);
+        assert_eq!(engine.cell_age(2, 1), 0);
+        assert_eq!(engine.cell_age(2, 3), 0);
+
+        engine.clear();
+        engine.spawn_cells(&[(2, 2)]);
+        assert_eq!(engine.cell_age(2, 2), 0);
+    }
+
+    #[test]
+    fn test_snapshot() {
+        let mut engine = Engine::<5, 5>::new();
+        engine.spawn_cells(&[(1, 2), (2, 2), (3, 2)]);
+        let snapshot = engine.snapshot();
+        engine.generate();
+
+        assert_eq!(snapshot.generation(), 0);
+        assert_eq!(snapshot.population(), 3);
+        assert!(snapshot.cell(1, 2).alive());
+        assert!(!snapshot.cell(2, 1).alive());
+        assert_eq!(
+            snapshot.live_coords().collect::<Vec<_>>(),
+            vec![(1, 2), (2, 2), (3, 2)]
+        );
+        // The engine itself moved on
+        assert!(engine.cells().cell(2, 1).alive());
+    }
+
+    #[test]
+    fn test_step_n() {
+        let mut stepped = Engine::<20, 20>::new();
+        stepped.randomize_seeded(3, 0.4);
+        let mut fast = Engine::<20, 20>::new();
+        fast.randomize_seeded(3, 0.4);
+
+        for _ in 0..10 {
+            stepped.step();
+        }
+        fast.step_n(10);
+
+        assert_eq!(fast.generation(), 10);
+        assert_eq!(fast.cells(), stepped.cells());
+        assert_eq!(fast.population(), stepped.population());
+    }
+
+    #[test]
+    fn test_generate_until_extinct() {
+        let mut lone = Engine::<5, 5>::new();
+        lone.spawn_cells(&[(2, 2)]);
+        assert!(!lone.is_extinct());
+        assert_eq!(lone.generate_until_extinct(10), Some(1));
+        assert!(lone.is_extinct());
+        // Already extinct
+        assert_eq!(lone.generate_until_extinct(10), Some(1));
+
+        let mut block = Engine::<5, 5>::new();
+        block.spawn_cells(&[(1, 1), (2, 1), (1, 2), (2, 2)]);
+        assert_eq!(block.generate_until_extinct(10), None);
+        assert_eq!(block.generation(), 10);
+    }
+
+    #[test]
+    fn test_clear() {
+        let mut engine = Engine::<20, 20>::new();
+        engine.randomize_seeded(3, 0.5);
+        for _ in 0..5 {
+            engine.generate();
+        }
+
+        engine.clear();
+        assert_eq!(engine.population(), 0);
+        assert_eq!(engine.generation(), 0);
+        assert!(engine.history().is_empty());
+        assert_eq!(
+            engine.cells().fingerprint(),
+            CellArray::<20, 20>::new().fingerprint()
+        );
+
+        // Nothing comes back from the back buffer
+        engine.generate();
+        assert_eq!(engine.population(), 0);
+    }
+
+    #[test]
+    fn test_render_ascii_glider() {
+        let mut engine = Engine::<6, 6>::new();
+        for &(x, y) in &[(2, 0), (2, 1), (2, 2), (1, 2), (0, 1)] {
+            engine.cells.spawn(x, y);
+        }
+        assert_eq!(
+            engine.render_ascii(),
+            "..#...\n\
+             #.#...\n\
+             .##...\n\
+             ......\n\
+             ......\n\
+             ......\n"
+        );
+
+        engine.generate();
+        assert_eq!(
+            engine.render_ascii(),
+            ".#....\n\
+             ..##..\n\
+             .##...\n\
+             ......\n\
+             ......\n\
+             ......\n"
+        );
+    }
+
+    #[test]
+    fn test_drip_seed() {
+        const PER_GEN: usize = 3;
+        const TOTAL: usize = 10;
+
+        let mut engine = Engine::<20, 20>::new();
+        engine.drip_seed(PER_GEN, TOTAL, 3);
+
+        engine.generate_dripping();
+        assert_eq!(engine.dripped(), PER_GEN);
+        assert_eq!(engine.population(), PER_GEN);
+
+        for _ in 0..10 {
+            engine.generate_dripping();
+        }
+        assert_eq!(engine.dripped(), TOTAL);
+        assert_eq!(engine.generation(), 11);
+    }
+
+    #[test]
+    fn test_generate_swaps_buffers() {
+        const H: usize = 100;
+        const W: usize = 100;
+        const COUNT: usize = 1000;
+        let mut engine = Engine::<H, W>::new();
+        engine.randomize_seeded(5, 0.5);
+
+        // The buffers trade places instead of being copied
+        let front = engine.cells() as *const CellArray<H, W>;
+        let back = &*engine.cell_cache as *const CellArray<H, W>;
+        engine.generate();
+        assert_eq!(engine.cells() as *const CellArray<H, W>, back);
+        assert_eq!(&*engine.cell_cache as *const CellArray<H, W>, front);
+        engine.generate();
+        assert_eq!(engine.cells() as *const CellArray<H, W>, front);
+
+        let (average_time, total_time) = measure_execution_time(|| engine.generate(), COUNT);
+        println!(
+            "Average time taken to generate {} cells once: {:?}",
+            H * W,
+            average_time
+        );
+        println!(
+            "Total time taken to generate {} cells {} times: {:?}",
+            H * W,
+            COUNT,
+            total_time
+        );
+    }
+
+    #[test]
+    fn test_count_emissions_gosper_gun() {
+        let mut engine = Engine::<64, 64>::new();
+        spawn_glider_gun(&mut engine.cells, 1, 1);
+
+        // The gun fires a glider every 30 generations,
+        // three of them are clear of the gun within 120
+        let emissions = engine.count_emissions((0, 0, 37, 10), 120);
+        assert_eq!(emissions, 3);
+    }
+
+    // Unpacked storage used to check the engine is independent of the bit layout
+    #[derive(Debug, Copy, Clone)]
+    struct WideCell {
+        alive: bool,
+        neighbours: u8,
+    }
+
+    impl CellStorage for WideCell {
+        fn empty() -> Self {
+            WideCell {
+                alive: false,
+                neighbours: 0,
+            }
+        }
+
+        fn is_alive(&self) -> bool {
+            self.alive
+        }
+
+        fn set_alive(&mut self, alive: bool) {
+            self.alive = alive;
+        }
+
+        fn neighbours(&self) -> u8 {
+            self.neighbours
+        }
+
+        fn set_neighbours(&mut self, count: u8) {
+            self.neighbours = count;
+        }
+    }
+
+    #[test]
+    fn test_alternate_storage_glider() {
+        let mut packed = Engine::<8, 8>::new();
+        let mut wide = Engine::<8, 8, WideCell>::new();
+        for &(x, y) in &[(2, 0), (2, 1), (2, 2), (1, 2), (0, 1)] {
+            packed.cells.spawn(x, y);
+            wide.cells.spawn(x, y);
+        }
+
+        for _ in 0..12 {
+            packed.generate();
+            wide.generate();
+
+            let packed_coords: Vec<_> = packed.cells().live_coords().collect();
+            let wide_coords: Vec<_> = wide.cells().live_coords().collect();
+            assert_eq!(packed_coords, wide_coords);
+            assert_eq!(packed.cells().fingerprint(), wide.cells().fingerprint());
+        }
+
+        // After 12 generations the glider has moved 3 cells diagonally
+        assert_eq!(
+            wide.cells().live_coords().collect::<Vec<_>>(),
+            vec![(5, 3), (3, 4), (5, 4), (4, 5), (5, 5)]
+        );
+    }
+
+    #[test]
+    fn test_population_delta() {
+        let mut blinker = Engine::<8, 8>::new();
+        spawn_blinker(&mut blinker.cells, 2, 2);
+        assert_eq!(blinker.population_delta(), 0);
+        for _ in 0..4 {
+            blinker.generate();
+            assert_eq!(blinker.population_delta(), 0);
+        }
+
+        // An L-tromino grows into a block, then stays a block
+        let mut tromino = Engine::<8, 8>::new();
+        for &(x, y) in &[(2, 2), (3, 2), (2, 3)] {
+            tromino.cells.spawn(x, y);
+        }
+        tromino.generate();
+        assert_eq!(tromino.population_delta(), 1);
+        tromino.generate();
+        assert_eq!(tromino.population_delta(), 0);
+
+        // A lone cell dies
+        let mut lone = Engine::<8, 8>::new();
+        lone.cells.spawn(4, 4);
+        lone.generate();
+        assert_eq!(lone.population_delta(), -1);
+
+        // Loading a board saved after some generations starts without a delta or history
+        tromino.generate();
+        let mut loaded = Engine::<8, 8>::new();
+        spawn_blinker(&mut loaded.cells, 2, 2);
+        loaded.step_n(5);
+        loaded.load_from_bytes(&lone.save_to_bytes()).unwrap();
+        assert_eq!(loaded.generation(), 1);
+        assert_eq!(loaded.population_delta(), 0);
+        assert!(loaded.history().is_empty());
+        loaded.load_from_bytes(&tromino.save_to_bytes()).unwrap();
+        assert_eq!(loaded.population_delta(), 0);
+        assert!(loaded.history().is_empty());
+        loaded.kill_cells(&[(2, 2)]);
+        loaded.generate();
+        assert_eq!(loaded.population_delta(), 1);
+        assert_eq!(loaded.history(), &[3, 4]);
+
+        // Undo and redo report the delta of the board they return to.
+        // An R-pentomino goes 5, 6, 7, 9.
+        let mut pentomino = Engine::<10, 10>::new();
+        pentomino.set_undo_capacity(4);
+        pentomino.spawn_cells(&[(5, 4), (6, 4), (4, 5), (5, 5), (5, 6)]);
+        pentomino.step_n(3);
+        assert_eq!(pentomino.population_delta(), 2);
+        assert!(pentomino.undo());
+        assert_eq!(pentomino.population(), 7);
+        assert_eq!(pentomino.population_delta(), 1);
+        assert!(pentomino.undo());
+        assert_eq!(pentomino.population(), 6);
+        assert_eq!(pentomino.population_delta(), 1);
+        assert!(pentomino.redo());
+        assert_eq!(pentomino.population_delta(), 1);
+        assert!(pentomino.redo());
+        assert_eq!(pentomino.population_delta(), 2);
+    }
+
+    #[test]
+    fn test_transition_breakdown() {
+        let mut engine = Engine::<8, 8>::new();
+        spawn_blinker(&mut engine.cells, 2, 2);
+        assert_eq!(
+            engine.transition_breakdown(),
+            TransitionBreakdown::default()
+        );
+
+        for _ in 0..3 {
+            engine.generate();
+            let breakdown = engine.transition_breakdown();
+
+            // The two ends die with a single neighbour,
+            // the two cells beside the centre are born with three
+            for (count, &(births, deaths)) in breakdown.iter().enumerate() {
+                assert_eq!(births, if count == 3 { 2 } else { 0 });
+                assert_eq!(deaths, if count == 1 { 2 } else { 0 });
+            }
+        }
+
+        // Overcrowding: the centre of a plus dies with four neighbours
+        let mut plus = Engine::<8, 8>::new();
+        for &(x, y) in &[(3, 3), (2, 3), (4, 3), (3, 2), (3, 4)] {
+            plus.cells.spawn(x, y);
+        }
+        plus.generate();
+        assert_eq!(plus.transition_breakdown()[4].1, 1);
+
+        // Wide cells hold counts above 8, here a lone cell with a count of 20
+        let mut wide = Engine::<5, 5, CellWide>::with_rule(Rule::new(&[20], &[], 2));
+        wide.cells.mut_cell(2, 2).set_neighbours(20);
+        wide.generate();
+        assert_eq!(wide.cells().live_coords().collect::<Vec<_>>(), vec![(2, 2)]);
+        assert_eq!(wide.transition_breakdown()[20], (1, 0));
+        wide.generate();
+        assert!(wide.is_extinct());
+        assert_eq!(wide.transition_breakdown()[0], (0, 1));
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        let mut engine = Engine::<12, 12>::new();
+        engine.set_undo_capacity(3);
+        spawn_glider(&mut engine.cells, 2, 2);
+
+        engine.generate();
+        let one = *engine.cells();
+        engine.generate();
+        let two = *engine.cells();
+
+        assert!(engine.undo());
+        assert_eq!(*engine.cells(), one);
+        assert_eq!(engine.generation(), 1);
+        assert!(engine.cells().verify_neighbour_counts());
+
+        assert!(engine.redo());
+        assert_eq!(*engine.cells(), two);
+        assert_eq!(engine.generation(), 2);
+        assert!(!engine.redo());
+
+        // Only the last 3 boards are kept
+        engine.step_n(5);
+        assert!(engine.undo() && engine.undo() && engine.undo());
+        assert!(!engine.undo());
+        assert_eq!(engine.generation(), 4);
+
+        // Generating drops the redo boards
+        engine.generate();
+        assert!(!engine.redo());
+        assert_eq!(engine.history().len(), 6);
+
+        // The back buffer follows, so the changes are those of the shown step
+        engine.step_n(2);
+        let mut expected = Engine::<12, 12>::new();
+        spawn_glider(&mut expected.cells, 2, 2);
+        expected.step_n(5);
+        expected.generate();
+        assert!(engine.undo() && engine.undo() && engine.redo());
+        assert_eq!(engine.generation(), 6);
+        assert_eq!(*engine.cells(), *expected.cells());
+        assert_eq!(
+            engine.changed_since_last_generation(),
+            expected.changed_since_last_generation()
+        );
+        assert!(!engine.changed_since_last_generation().is_empty());
+
+        // The saved generation comes back even if the counter was changed in between
+        engine.generation = 0;
+        assert!(engine.undo());
+        assert_eq!(engine.generation(), 5);
+
+        let mut off = Engine::<5, 5>::new();
+        off.generate();
+        assert!(!off.undo());
+    }
+
+    #[test]
+    fn test_search_soups() {
+        let result = Engine::<16, 16>::search_soups(6, 200, 100).unwrap();
+        assert_eq!(
+            Some(&result),
+            Engine::<16, 16>::search_soups(6, 200, 100).as_ref()
+        );
+        assert_eq!(Engine::<16, 16>::search_soups(0, 200, 100), None);
+
+        let seeds: Vec<u64> = result.trials.iter().map(|trial| trial.seed).collect();
+        assert_eq!(seeds, (100..106).collect::<Vec<_>>());
+        assert!(result.trials.contains(&result.best));
+        for trial in &result.trials {
+            assert!(trial.lifespan <= 200);
+            assert!(trial.stabilized || trial.lifespan == 200);
+            assert!(trial.lifespan <= result.best.lifespan);
+        }
+
+        // The best soup replays the same way
+        let mut engine = Engine::<16, 16>::new();
+        engine.randomize_seeded(result.best.seed, 0.5);
+        engine.generate_until_stable(200, SOUP_MAX_PERIOD);
+        assert_eq!(engine.population(), result.best.population);
+    }
+
+    #[test]
+    fn test_generate_with_events() {
+        let mut engine = Engine::<5, 5>::new();
+        engine.spawn_cells(&[(1, 2), (2, 2), (3, 2)]);
+
+        let events = engine.generate_with_events();
+        assert_eq!(events.born, vec![(2, 1), (2, 3)]);
+        assert_eq!(events.died, vec![(1, 2), (3, 2)]);
+
+        let events = engine.generate_with_events();
+        assert_eq!(events.born, vec![(1, 2), (3, 2)]);
+        assert_eq!(events.died, vec![(2, 1), (2, 3)]);
+        assert_eq!(engine.generation(), 2);
+    }
+
+    #[test]
+    fn test_blinker_across_the_seam() {
+        const W: usize = 8;
+        let horizontal = [(W as isize - 1, 2), (0, 2), (1, 2)];
+        let vertical = [(0, 1), (0, 2), (0, 3)];
+
+        let mut engine = Engine::<6, W>::new();
+        let mut sparse = Engine::<6, W>::new();
+        engine.spawn_cells(&horizontal);
+        sparse.spawn_cells(&horizontal);
+
+        for generation in 1..=6 {
+            engine.generate();
+            sparse.generate_sparse();
+
+            let expected = if generation % 2 == 1 {
+                &vertical
+            } else {
+                &horizontal
+            };
+            let mut expected: Vec<_> = expected.to_vec();
+            expected.sort_by_key(|&(x, y)| (y, x));
+            assert_eq!(engine.cells().live_coords().collect::<Vec<_>>(), expected);
+            assert_eq!(sparse.cells(), engine.cells());
+            assert!(engine.cells().verify_neighbour_counts());
+        }
+    }
+
+    #[test]
+    fn test_glider_across_the_corner() {
+        // A glider moves one cell down and right every 4 generations,
+        // so it crosses both seams and is back where it started after 4 * 10
+        let mut engine = Engine::<10, 10>::new();
+        let mut sparse = Engine::<10, 10>::new();
+        let glider: Vec<_> = Glider
+            .cells()
+            .iter()
+            .map(|&(x, y)| (x + 7, y + 7))
+            .collect();
+        engine.spawn_cells(&glider);
+        sparse.spawn_cells(&glider);
+        let start = *engine.cells();
+
+        for generation in 1..=40 {
+            engine.generate();
+            sparse.generate_sparse();
+            assert_eq!(engine.population(), 5, "generation {}", generation);
+            assert_eq!(sparse.cells(), engine.cells());
+            if generation == 12 {
+                // Wrapped into the top left corner
+                let coords: Vec<_> = engine.cells().live_coords().collect();
+                assert!(coords.iter().all(|&(x, y)| x < 3 && y < 3), "{:?}", coords);
+            }
+        }
+        assert_eq!(*engine.cells(), start);
+        assert!(engine.cells().verify_neighbour_counts());
+    }
+
+    #[test]
+    fn test_brians_brain() {
+        // The smallest spaceship: two alive cells pulling two dying cells behind them
+        let mut engine = Engine::<5, 10>::with_rule(Rule::brians_brain());
+        engine.spawn_cells(&[(2, 1), (2, 2)]);
+        engine.cells.mut_cell(1, 1).set_dying(1);
+        engine.cells.mut_cell(1, 2).set_dying(1);
+
+        for step in 1..=3 {
+            engine.generate_sparse();
+            let x = 2 + step;
+            assert_eq!(
+                engine.cells().live_coords().collect::<Vec<_>>(),
+                vec![(x, 1), (x, 2)]
+            );
+            for y in 0..5 {
+                for cx in 0..10 {
+                    let expected = if cx == x - 1 && (y == 1 || y == 2) {
+                        1
+                    } else {
+                        0
+                    };
+                    assert_eq!(
+                        engine.cells().cell(cx, y).dying(),
+                        expected,
+                        "({}, {})",
+                        cx,
+                        y
+                    );
+                }
+            }
+        }
+
+        // Every alive cell dies and spends a generation dying
+        let mut engine = Engine::<5, 5>::with_rule(Rule::brians_brain());
+        engine.spawn_cells(&[(2, 2)]);
+        engine.generate();
+        assert_eq!(engine.population(), 0);
+        assert_eq!(engine.cells().cell(2, 2).dying(), 1);
+        engine.generate();
+        assert_eq!(engine.cells().cell(2, 2).dying(), 0);
+    }
+
+    #[test]
+    fn test_from_cells() {
+        let mut cells = CellArray::<8, 8>::new();
+        spawn_glider(&mut cells, 1, 1);
+
+        let mut engine = Engine::from_cells(cells);
+        assert_eq!(engine.generation(), 0);
+        assert_eq!(engine.population(), 5);
+
+        engine.generate();
+        assert_eq!(
+            engine.cells().live_coords().collect::<Vec<_>>(),
+            vec![(2, 1), (3, 2), (4, 2), (2, 3), (3, 3)]
+        );
+
+        // A glider moves one cell down and right every 4 generations
+        engine.step_n(3);
+        let mut moved = CellArray::<8, 8>::new();
+        spawn_glider(&mut moved, 2, 2);
+        assert_eq!(*engine.cells(), moved);
+        assert!(engine.cells().verify_neighbour_counts());
+    }
+
+    #[test]
+    fn test_classify_objects() {
+        let mut engine = Engine::<20, 20>::new();
+        engine.spawn_cells(&[(3, 3), (4, 3), (3, 4), (4, 4)]);
+        // The second block straddles the corner of the board
+        engine.spawn_cells(&[(19, 19), (0, 19), (19, 0), (0, 0)]);
+        spawn_blinker(&mut engine.cells, 10, 10);
+        assert_eq!(
+            engine.classify_objects(),
+            ObjectCensus {
+                blocks: 2,
+                blinkers: 1,
+                ..ObjectCensus::default()
+            }
+        );
+
+        // The blinker in its other phase, a rotated loaf and a glider
+        engine.generate();
+        stamp_oriented(&Loaf, &mut engine.cells, 6, 8, Rotation::R90, Flip::None);
+        spawn_glider(&mut engine.cells, 14, 2);
+        stamp(&Beehive, &mut engine.cells, 3, 14);
+        assert_eq!(
+            engine.classify_objects(),
+            ObjectCensus {
+                blocks: 2,
+                beehives: 1,
+                loaves: 1,
+                blinkers: 1,
+                unknown: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn test_with_topology() {
+        let glider_run = |topology| {
+            let mut engine = Engine::<8, 8>::with_topology(topology);
+            spawn_glider(&mut engine.cells, 1, 1);
+            engine.step_n(32);
+            assert!(engine.cells().verify_neighbour_counts());
+            engine
+        };
+
+        // 32 generations carry the glider once around the board
+        let toroidal = glider_run(Topology::Toroidal);
+        let mut start = CellArray::<8, 8>::new();
+        spawn_glider(&mut start, 1, 1);
+        assert_eq!(*toroidal.cells(), start);
+
+        // It runs into the corner and leaves a block behind
+        let fixed = glider_run(Topology::Fixed);
+        assert_eq!(fixed.topology(), Topology::Fixed);
+        assert_eq!(
+            fixed.cells().live_coords().collect::<Vec<_>>(),
+            vec![(6, 6), (7, 6), (6, 7), (7, 7)]
+        );
+    }
+
+    #[test]
+    fn test_generations() {
+        let mut engine = Engine::<8, 8>::new();
+        spawn_blinker(&mut engine.cells, 2, 2);
+
+        let snapshots: Vec<_> = engine.generations_mut().take(3).collect();
+        let numbers: Vec<_> = snapshots.iter().map(|s| s.generation()).collect();
+        assert_eq!(numbers, vec![1, 2, 3]);
+        assert_eq!(engine.generation(), 3);
+        assert!(snapshots.iter().all(|s| s.population() == 3));
+        // The blinker flips back and forth
+        assert!(snapshots[0].cell(3, 1).is_alive() && !snapshots[1].cell(3, 1).is_alive());
+
+        let mut glider = Engine::<10, 10>::new();
+        spawn_glider(&mut glider.cells, 0, 0);
+        let fourth = glider.generations().nth(3).unwrap();
+        assert_eq!(fourth.generation(), 4);
+        assert_eq!(
+            fourth.live_coords().collect::<Vec<_>>(),
+            vec![(3, 1), (1, 2), (3, 2), (2, 3), (3, 3)]
+        );
+    }
+
+    #[test]
+    fn test_tick() {
+        let mut engine = Engine::<20, 20>::new();
+        engine.randomize_seeded(3, 0.3);
+
+        let summaries: Vec<_> = (0..100).map(|_| engine.tick()).collect();
+        for (i, summary) in summaries.iter().enumerate() {
+            assert_eq!(summary.generation, i + 1);
+        }
+        assert_eq!(summaries[99].population, engine.population());
+
+        let mut engine = Engine::<8, 8>::new();
+        spawn_blinker(&mut engine.cells, 2, 2);
+        let summary = engine.tick();
+        assert!(!summary.stable && !summary.extinct);
+        assert_eq!(summary.population, 3);
+
+        engine.clear();
+        engine.spawn_cells(&[(1, 1), (2, 1), (1, 2), (2, 2)]);
+        assert!(engine.tick().stable);
+
+        engine.spawn_cells(&[(6, 6)]);
+        engine.kill_cells(&[(1, 1), (2, 1), (1, 2), (2, 2)]);
+        assert_eq!(
+            engine.tick(),
+            TickSummary {
+                generation: 2,
+                population: 0,
+                stable: false,
+                extinct: true
+            }
+        );
+        assert!(engine.tick().stable);
+    }
+
+    #[test]
+    fn test_next_cell_state() {
+        // (2, 2) with the first n of its neighbours alive
+        let with_neighbours = |alive: bool, n: usize| {
+            let mut engine = Engine::<5, 5>::new();
+            let neighbours = engine.cells().neighbour_coordinates(2, 2);
+            engine.spawn_cells(&neighbours[..n]);
+            if alive {
+                engine.spawn_cells(&[(2, 2)]);
+            }
+            engine.next_cell_state(2, 2)
+        };
+
+        assert!(!with_neighbours(true, 1));
+        assert!(with_neighbours(true, 2));
+        assert!(with_neighbours(true, 3));
+        assert!(!with_neighbours(true, 4));
+        assert!(!with_neighbours(false, 2));
+        assert!(with_neighbours(false, 3));
+        assert!(!with_neighbours(false, 4));
+
+        // Matches what generate does for every cell
+        let mut engine = Engine::<10, 10>::with_rule(Rule::highlife());
+        engine.randomize_seeded(5, 0.4);
+        let expected: Vec<bool> = (0..100)
+            .map(|i| engine.next_cell_state(i % 10, i / 10))
+            .collect();
+        engine.generate();
+        for (i, &alive) in expected.iter().enumerate() {
+            let (x, y) = (i as isize % 10, i as isize / 10);
+            assert_eq!(engine.cells().cell(x, y).alive(), alive, "({}, {})", x, y);
+        }
+    }
+
+    #[test]
+    fn test_is_still_life_region() {
+        let mut engine = Engine::<12, 12>::new();
+        engine.spawn_cells(&[(1, 1), (2, 1), (1, 2), (2, 2)]);
+        spawn_blinker(&mut engine.cells, 6, 6);
+
+        assert!(engine.is_still_life_region(0, 0, 3, 3));
+        assert!(!engine.is_still_life_region(5, 5, 9, 7));
+        assert!(!engine.is_still_life_region(0, 0, 11, 11));
+        // Empty space away from both
+        assert!(engine.is_still_life_region(0, 9, 11, 11));
+        assert_eq!(engine.generation(), 0);
+    }
+
+    #[test]
+    fn test_visit_count() {
+        const GENERATIONS: u32 = 7;
+        let mut engine = Engine::<6, 6>::new();
+        let block = [(2, 2), (3, 2), (2, 3), (3, 3)];
+        engine.spawn_cells(&block);
+        engine.step_n(GENERATIONS as usize);
+
+        for y in 0..6 {
+            for x in 0..6 {
+                let expected = if block.contains(&(x, y)) {
+                    GENERATIONS
+                } else {
+                    0
+                };
+                assert_eq!(engine.visit_count(x, y), expected, "({}, {})", x, y);
+            }
+        }
+
+        engine.restart();
+        assert_eq!(engine.visit_count(2, 2), GENERATIONS);
+        engine.reset_heatmap();
+        assert!(engine.visit_counts().iter().all(|&count| count == 0));
+    }
+
+    #[test]
+    fn test_set_rule() {
+        // A block is still under B3/S23, under B2/S it dies
+        // and the 8 cells touching two of its sides are born
+        let mut engine = Engine::<8, 8>::new();
+        engine.spawn_cells(&[(3, 3), (4, 3), (3, 4), (4, 4)]);
+        engine.generate();
+        assert_eq!(engine.population(), 4);
+
+        engine.set_rule(Rule::seeds());
+        assert_eq!(*engine.rule(), Rule::seeds());
+        engine.generate();
+        assert_eq!(
+            engine.cells().live_coords().collect::<Vec<_>>(),
+            vec![
+                (3, 2),
+                (4, 2),
+                (2, 3),
+                (5, 3),
+                (2, 4),
+                (5, 4),
+                (3, 5),
+                (4, 5)
+            ]
+        );
+        assert!(engine.cells().verify_neighbour_counts());
+
+        // Dying cells of Brian's Brain do not outlive the rule
+        let mut engine = Engine::<5, 5>::with_rule(Rule::brians_brain());
+        engine.spawn_cells(&[(2, 2)]);
+        engine.generate();
+        assert_eq!(engine.cells().cell(2, 2).dying(), 1);
+        engine.set_rule(Rule::conway());
+        assert_eq!(engine.cells().cell(2, 2).dying(), 0);
+        assert!(engine.cells().verify_neighbour_counts());
+    }
+
+    #[test]
+    fn test_next_generation_into_matches_generate() {
+        for seed in 0..5 {
+            let mut engine = Engine::<40, 60>::new();
+            engine.randomize_seeded(seed, 0.3);
+            let mut next = CellArray::new();
+
+            for _ in 0..20 {
+                engine
+                    .cells
+                    .next_generation_into(&mut next, &Rule::conway());
+                engine.generate();
+                assert_eq!(next, *engine.cells(), "seed {}", seed);
+            }
+        }
+    }
+
+    #[test]
+    fn test_generate_sparse_matches_generate() {
+        for seed in 0..5 {
+            let mut dense = Engine::<24, 32>::new();
+            let mut sparse = Engine::<24, 32>::new();
+            // Leave most rows empty so rows actually get skipped
+            let mut rng = StdRng::seed_from_u64(seed);
+            for _ in 0..20 {
+                let x: isize = rng.gen_range(0..32);
+                let y: isize = rng.gen_range(8..14);
+                dense.cells.spawn(x, y);
+                sparse.cells.spawn(x, y);
+            }
+
+            for _ in 0..30 {
+                dense.generate();
+                sparse.generate_sparse();
+                assert_eq!(sparse.cells().fingerprint(), dense.cells().fingerprint());
+                assert_eq!(sparse.transition_breakdown(), dense.transition_breakdown());
+            }
+        }
+    }
+
+    #[test]
+    fn test_generate_sparse_twisted() {
+        // Crossing the right edge jumps 5 rows down, far from the rows next to it
+        let topology = Topology::Twisted {
+            shift_x: 0,
+            shift_y: 5,
+        };
+        for seed in 0..5 {
+            let mut dense = Engine::<12, 10>::with_topology(topology);
+            let mut sparse = Engine::<12, 10>::with_topology(topology);
+            let mut rng = StdRng::seed_from_u64(seed);
+            for _ in 0..8 {
+                let x: isize = rng.gen_range(7..10);
+                let y: isize = rng.gen_range(0..3);
+                dense.cells.spawn(x, y);
+                sparse.cells.spawn(x, y);
+            }
+
+            for _ in 0..20 {
+                dense.generate();
+                sparse.generate_sparse();
+                assert_eq!(*sparse.cells(), *dense.cells(), "seed {}", seed);
+                assert!(sparse.cells().verify_neighbour_counts());
+            }
+        }
+    }
+
+    #[test]
+    fn test_generate_sparse_glider_gun() {
+        let mut dense = Engine::<40, 60>::new();
+        let mut sparse = Engine::<40, 60>::new();
+        spawn_glider_gun(&mut dense.cells, 10, 10);
+        spawn_glider_gun(&mut sparse.cells, 10, 10);
+
+        for _ in 0..5 {
+            dense.generate();
+            sparse.generate_sparse();
+            assert_eq!(*sparse.cells(), *dense.cells());
+        }
+        assert_eq!(sparse.generation(), dense.generation());
     }
 
     #[test]
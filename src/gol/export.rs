@@ -0,0 +1,412 @@
+#[cfg(any(feature = "image", feature = "gif"))]
+use std::path::Path;
+#[cfg(feature = "gif")]
+use std::{borrow::Cow, fmt, fs::File, io};
+
+use crate::gol::display::{COLOR_ALIVE, COLOR_DEAD};
+use crate::gol::engine::Engine;
+#[cfg(any(feature = "image", feature = "gif"))]
+use crate::gol::types::CellArray;
+
+#[cfg(feature = "image")]
+use image::{ImageError, Rgb, RgbImage};
+
+const COLOR_BORN: [u8; 3] = [0x00, 0xFF, 0x00]; // Green
+const COLOR_DIED: [u8; 3] = [0xFF, 0x00, 0x00]; // Red
+const COLOR_SURVIVED: [u8; 3] = [0xFF, 0xFF, 0xFF]; // White
+const COLOR_EMPTY: [u8; 3] = [0x00, 0x00, 0x00]; // Black
+
+// Heatmap gradient from cells that were never alive to the most visited ones
+const HEAT_GRADIENT: [[u8; 3]; 3] = [
+    [0x00, 0x00, 0x00], // Black
+    [0xFF, 0x00, 0x00], // Red
+    [0xFF, 0xFF, 0x00], // Yellow
+];
+
+// Split a 0xRRGGBB color into its channels
+pub fn rgb(color: u32) -> [u8; 3] {
+    [(color >> 16) as u8, (color >> 8) as u8, color as u8]
+}
+
+// Color of a cell in exported images, the same as in the Display window
+pub fn cell_rgb(alive: bool) -> [u8; 3] {
+    rgb(if alive { COLOR_ALIVE } else { COLOR_DEAD })
+}
+
+impl<const H: usize, const W: usize> Engine<H, W> {
+    // Board as (W * scale) x (H * scale) RGBA pixels, row by row, fully opaque.
+    // The same colors as the Display window, without needing a window,
+    // e.g. for a browser canvas. A scale of 0 is an empty 0 x 0 image.
+    pub fn render_rgba(&self, scale: usize) -> Vec<u8> {
+        if scale == 0 {
+            return Vec::new();
+        }
+        let width = W * scale;
+        let mut rgba = vec![0; width * H * scale * 4];
+
+        for (py, row) in rgba.chunks_exact_mut(width * 4).enumerate() {
+            for (px, pixel) in row.chunks_exact_mut(4).enumerate() {
+                let alive = self
+                    .cells
+                    .cell((px / scale) as isize, (py / scale) as isize)
+                    .alive();
+                let [r, g, b] = cell_rgb(alive);
+                pixel.copy_from_slice(&[r, g, b, 0xFF]);
+            }
+        }
+        rgba
+    }
+}
+
+// Color of a visit count on a gradient that ends at `max`
+pub fn heat_rgb(count: u32, max: u32) -> [u8; 3] {
+    if max == 0 {
+        return HEAT_GRADIENT[0];
+    }
+    // Position on the gradient in 1/1000ths of the way between two stops
+    let segments = (HEAT_GRADIENT.len() - 1) as u64;
+    let position = count.min(max) as u64 * segments * 1000 / max as u64;
+    let stop = ((position / 1000) as usize).min(HEAT_GRADIENT.len() - 2);
+    let t = position - stop as u64 * 1000;
+
+    let (from, to) = (HEAT_GRADIENT[stop], HEAT_GRADIENT[stop + 1]);
+    std::array::from_fn(|i| ((from[i] as u64 * (1000 - t) + to[i] as u64 * t) / 1000) as u8)
+}
+
+// What happened to a single cell between two generations
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Transition {
+    Born,
+    Died,
+    Survived,
+    Empty,
+}
+
+impl Transition {
+    pub fn classify(before: bool, after: bool) -> Transition {
+        match (before, after) {
+            (false, true) => Transition::Born,
+            (true, false) => Transition::Died,
+            (true, true) => Transition::Survived,
+            (false, false) => Transition::Empty,
+        }
+    }
+
+    pub fn rgb(&self) -> [u8; 3] {
+        match self {
+            Transition::Born => COLOR_BORN,
+            Transition::Died => COLOR_DIED,
+            Transition::Survived => COLOR_SURVIVED,
+            Transition::Empty => COLOR_EMPTY,
+        }
+    }
+}
+
+// Paint an image of (W * scale) x (H * scale) pixels,
+// every cell becomes a scale x scale block of the color returned for it
+#[cfg(feature = "image")]
+fn block_image<const H: usize, const W: usize>(
+    scale: usize,
+    color: impl Fn(isize, isize) -> [u8; 3],
+) -> RgbImage {
+    let mut image = RgbImage::new((W * scale) as u32, (H * scale) as u32);
+
+    for y in 0..H {
+        for x in 0..W {
+            let pixel = Rgb(color(x as isize, y as isize));
+
+            for py in y * scale..(y + 1) * scale {
+                for px in x * scale..(x + 1) * scale {
+                    image.put_pixel(px as u32, py as u32, pixel);
+                }
+            }
+        }
+    }
+
+    image
+}
+
+// Render the board with the alive and dead colors of the Display window
+#[cfg(feature = "image")]
+pub fn board_image<const H: usize, const W: usize>(
+    cells: &CellArray<H, W>,
+    scale: usize,
+) -> RgbImage {
+    block_image::<H, W>(scale, |x, y| cell_rgb(cells.cell(x, y).alive()))
+}
+
+// Render the transitions between two boards as an image.
+// Each cell becomes a scale x scale block colored by its Transition.
+#[cfg(feature = "image")]
+pub fn diff_image<const H: usize, const W: usize>(
+    before: &CellArray<H, W>,
+    after: &CellArray<H, W>,
+    scale: usize,
+) -> RgbImage {
+    block_image::<H, W>(scale, |x, y| {
+        Transition::classify(before.cell(x, y).alive(), after.cell(x, y).alive()).rgb()
+    })
+}
+
+// Write the diff_image of two boards to a PNG file
+#[cfg(feature = "image")]
+pub fn diff_png<const H: usize, const W: usize>(
+    before: &CellArray<H, W>,
+    after: &CellArray<H, W>,
+    path: &Path,
+    scale: usize,
+) -> Result<(), ImageError> {
+    diff_image(before, after, scale).save(path)
+}
+
+#[cfg(feature = "image")]
+impl<const H: usize, const W: usize> Engine<H, W> {
+    // Write the current board to a PNG file, each cell as a scale x scale block
+    pub fn save_png(&self, path: &Path, scale: usize) -> Result<(), ImageError> {
+        board_image(self.cells(), scale).save(path)
+    }
+
+    // Write the visit counts to a PNG file, each cell as a scale x scale block
+    // colored by heat_rgb relative to the most visited cell
+    pub fn save_heatmap_png(&self, path: &Path, scale: usize) -> Result<(), ImageError> {
+        let max = self.visit_counts().iter().copied().max().unwrap_or(0);
+        block_image::<H, W>(scale, |x, y| heat_rgb(self.visit_count(x, y), max)).save(path)
+    }
+}
+
+#[cfg(feature = "gif")]
+#[derive(Debug)]
+pub enum GifError {
+    Io(io::Error),
+    Encoding(gif::EncodingError),
+    // GIF dimensions are limited to u16
+    TooLarge { width: usize, height: usize },
+}
+
+#[cfg(feature = "gif")]
+impl fmt::Display for GifError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GifError::Io(err) => write!(f, "could not write gif: {}", err),
+            GifError::Encoding(err) => write!(f, "could not encode gif: {}", err),
+            GifError::TooLarge { width, height } => {
+                write!(f, "{}x{} is too large for a gif", width, height)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "gif")]
+impl std::error::Error for GifError {}
+
+#[cfg(feature = "gif")]
+impl From<io::Error> for GifError {
+    fn from(err: io::Error) -> Self {
+        GifError::Io(err)
+    }
+}
+
+#[cfg(feature = "gif")]
+impl From<gif::EncodingError> for GifError {
+    fn from(err: gif::EncodingError) -> Self {
+        GifError::Encoding(err)
+    }
+}
+
+// Palette indices of a scaled board, 0 is dead and 1 is alive.
+// The palette itself comes from cell_rgb, so frames match save_png.
+#[cfg(feature = "gif")]
+fn palette_indices<const H: usize, const W: usize>(
+    cells: &CellArray<H, W>,
+    scale: usize,
+) -> Vec<u8> {
+    let mut indices = Vec::with_capacity(H * W * scale * scale);
+    for py in 0..H * scale {
+        for px in 0..W * scale {
+            let alive = cells
+                .cell((px / scale) as isize, (py / scale) as isize)
+                .alive();
+            indices.push(alive as u8);
+        }
+    }
+    indices
+}
+
+#[cfg(feature = "gif")]
+impl<const H: usize, const W: usize> Engine<H, W> {
+    // Write an animated GIF of the current and the following generations.
+    // The engine is advanced between frames, so it ends up
+    // generations - 1 generations further. delay_cs is in 1/100 s.
+    pub fn record_gif(
+        &mut self,
+        path: &Path,
+        generations: usize,
+        scale: usize,
+        delay_cs: u16,
+    ) -> Result<(), GifError> {
+        let (width, height) = (W * scale, H * scale);
+        if width > u16::MAX as usize || height > u16::MAX as usize {
+            return Err(GifError::TooLarge { width, height });
+        }
+
+        let palette = [cell_rgb(false), cell_rgb(true)].concat();
+        let file = File::create(path)?;
+        let mut encoder = gif::Encoder::new(file, width as u16, height as u16, &palette)?;
+        encoder.set_repeat(gif::Repeat::Infinite)?;
+
+        for frame in 0..generations {
+            if frame > 0 {
+                self.generate();
+            }
+
+            let frame = gif::Frame {
+                width: width as u16,
+                height: height as u16,
+                delay: delay_cs,
+                buffer: Cow::Owned(palette_indices(self.cells(), scale)),
+                ..gif::Frame::default()
+            };
+            encoder.write_frame(&frame)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod rgba_tests {
+    use super::*;
+
+    #[test]
+    fn test_heat_rgb() {
+        assert_eq!(heat_rgb(0, 0), HEAT_GRADIENT[0]);
+        assert_eq!(heat_rgb(0, 10), HEAT_GRADIENT[0]);
+        assert_eq!(heat_rgb(5, 10), HEAT_GRADIENT[1]);
+        assert_eq!(heat_rgb(10, 10), HEAT_GRADIENT[2]);
+        assert_eq!(heat_rgb(20, 10), HEAT_GRADIENT[2]);
+        assert_eq!(heat_rgb(1, 4), [0x7F, 0x00, 0x00]);
+    }
+
+    #[test]
+    fn test_render_rgba() {
+        const SCALE: usize = 2;
+        let mut engine = Engine::<3, 4>::new();
+        engine.spawn_cells(&[(2, 1)]);
+
+        let rgba = engine.render_rgba(SCALE);
+        assert_eq!(rgba.len(), 4 * SCALE * 3 * SCALE * 4);
+
+        let stride = 4 * SCALE * 4;
+        let pixel = |x: usize, y: usize| &rgba[y * stride + x * 4..y * stride + x * 4 + 4];
+        let [r, g, b] = rgb(COLOR_ALIVE);
+        for (x, y) in [(4, 2), (5, 2), (4, 3), (5, 3)] {
+            assert_eq!(pixel(x, y), [r, g, b, 0xFF], "({}, {})", x, y);
+        }
+        let [r, g, b] = rgb(COLOR_DEAD);
+        for (x, y) in [(3, 2), (6, 2), (4, 1), (5, 4), (0, 0)] {
+            assert_eq!(pixel(x, y), [r, g, b, 0xFF], "({}, {})", x, y);
+        }
+
+        assert!(engine.render_rgba(0).is_empty());
+    }
+}
+
+#[cfg(all(test, feature = "gif"))]
+mod gif_tests {
+    use super::*;
+    use crate::gol::patterns::spawn_blinker;
+
+    #[test]
+    fn test_record_blinker_gif() {
+        let mut engine = Engine::<5, 5>::new();
+        spawn_blinker(&mut engine.cells, 1, 2);
+
+        let path = std::env::temp_dir().join("gol_test_blinker.gif");
+        engine.record_gif(&path, 5, 2, 10).unwrap();
+        assert_eq!(engine.generation(), 4);
+
+        let file = File::open(&path).unwrap();
+        assert!(file.metadata().unwrap().len() > 0);
+
+        let mut decoder = gif::DecodeOptions::new().read_info(file).unwrap();
+        let mut frames = 0;
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            assert_eq!(frame.delay, 10);
+            frames += 1;
+        }
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(frames, 5);
+    }
+}
+
+#[cfg(all(test, feature = "image"))]
+mod tests {
+    use super::*;
+    use crate::gol::patterns::{spawn_blinker, spawn_glider};
+
+    #[test]
+    fn test_save_png() {
+        const SCALE: usize = 3;
+        let mut engine = Engine::<8, 8>::new();
+        spawn_glider(&mut engine.cells, 0, 0);
+
+        let path = std::env::temp_dir().join("gol_test_glider.png");
+        engine.save_png(&path, SCALE).unwrap();
+        let image = image::open(&path).unwrap().to_rgb8();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(image.dimensions(), (24, 24));
+        // (2, 0) is alive, (0, 0) is dead
+        assert_eq!(image.get_pixel(2 * 3 + 1, 1).0, [0xFF, 0xFF, 0xFF]);
+        assert_eq!(image.get_pixel(1, 1).0, [0x00, 0x00, 0x00]);
+    }
+
+    #[test]
+    fn test_save_heatmap_png() {
+        let mut engine = Engine::<6, 6>::new();
+        spawn_blinker(&mut engine.cells, 1, 2);
+        engine.step_n(4);
+
+        let path = std::env::temp_dir().join("gol_test_heatmap.png");
+        engine.save_heatmap_png(&path, 2).unwrap();
+        let image = image::open(&path).unwrap().to_rgb8();
+        std::fs::remove_file(&path).unwrap();
+
+        // The centre is always alive, the tips half of the time
+        assert_eq!(image.dimensions(), (12, 12));
+        assert_eq!(image.get_pixel(2 * 2, 2 * 2).0, HEAT_GRADIENT[2]);
+        assert_eq!(image.get_pixel(2 * 2, 2).0, HEAT_GRADIENT[1]);
+        assert_eq!(image.get_pixel(0, 0).0, HEAT_GRADIENT[0]);
+    }
+
+    #[test]
+    fn test_blinker_diff_png() {
+        const H: usize = 5;
+        const W: usize = 5;
+        const SCALE: usize = 2;
+
+        // Vertical blinker and the horizontal phase it flips to
+        let mut before = CellArray::<H, W>::new();
+        let mut after = CellArray::<H, W>::new();
+        for i in 1..4 {
+            before.spawn(2, i);
+            after.spawn(i, 2);
+        }
+
+        let path = std::env::temp_dir().join("gol_test_blinker_diff.png");
+        diff_png(&before, &after, &path, SCALE).unwrap();
+        let image = image::open(&path).unwrap().to_rgb8();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(image.dimensions(), ((W * SCALE) as u32, (H * SCALE) as u32));
+        assert!(image.pixels().any(|p| p.0 == COLOR_BORN));
+        assert!(image.pixels().any(|p| p.0 == COLOR_DIED));
+
+        // The centre survives, the ends of the vertical bar die
+        let centre = (2 * SCALE) as u32;
+        assert_eq!(image.get_pixel(centre, centre).0, COLOR_SURVIVED);
+        assert_eq!(image.get_pixel(centre, SCALE as u32).0, COLOR_DIED);
+        assert_eq!(image.get_pixel(SCALE as u32, centre).0, COLOR_BORN);
+    }
+}
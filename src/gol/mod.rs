@@ -1,6 +1,28 @@
 pub mod types;
+#[cfg(feature = "std")]
 pub mod engine;
+#[cfg(feature = "std")]
+pub mod dyn_engine;
+#[cfg(feature = "std")]
 pub mod display;
+#[cfg(feature = "std")]
+pub mod export;
+#[cfg(feature = "net")]
+pub mod net;
+#[cfg(feature = "std")]
+pub mod parse;
+#[cfg(feature = "std")]
+pub mod patterns;
+#[cfg(feature = "std")]
+pub mod scenario;
+#[cfg(feature = "std")]
+pub mod serialize;
 
+#[cfg(feature = "std")]
 pub use engine::*;
-pub use display::*;
\ No newline at end of file
+#[cfg(feature = "std")]
+pub use dyn_engine::*;
+#[cfg(feature = "std")]
+pub use display::*;
+#[cfg(feature = "std")]
+pub use serialize::*;
\ No newline at end of file
@@ -0,0 +1,241 @@
+// Keeps remote copies of a board in sync over TCP.
+//
+// The server sends one update per generation, every update is:
+//  [generation: u64] [count: u32] followed by `count` changes of
+//  [x: u32] [y: u32] [state: u8]
+// All integers are little endian, a state of 1 means alive and 0 dead.
+// Only the cells that changed are sent, except for the first update
+// after a client connects which spawns every alive cell.
+
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+
+use crate::gol::engine::Engine;
+use crate::gol::types::CellArray;
+
+const CHANGE_LEN: usize = 4 + 4 + 1;
+
+// Cells whose alive state differs between the two boards, as (x, y, alive after)
+pub fn diff<const H: usize, const W: usize>(
+    before: &CellArray<H, W>,
+    after: &CellArray<H, W>,
+) -> Vec<(u32, u32, bool)> {
+    let mut changes = Vec::new();
+    for y in 0..H {
+        for x in 0..W {
+            let alive = after.cell(x as isize, y as isize).alive();
+            if before.cell(x as isize, y as isize).alive() != alive {
+                changes.push((x as u32, y as u32, alive));
+            }
+        }
+    }
+    changes
+}
+
+fn encode(generation: usize, changes: &[(u32, u32, bool)]) -> Vec<u8> {
+    let mut data = Vec::with_capacity(8 + 4 + changes.len() * CHANGE_LEN);
+    data.extend_from_slice(&(generation as u64).to_le_bytes());
+    data.extend_from_slice(&(changes.len() as u32).to_le_bytes());
+    for &(x, y, alive) in changes {
+        data.extend_from_slice(&x.to_le_bytes());
+        data.extend_from_slice(&y.to_le_bytes());
+        data.push(alive as u8);
+    }
+    data
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+pub struct Server {
+    listener: TcpListener,
+    clients: Vec<BufWriter<TcpStream>>,
+}
+
+impl Server {
+    pub fn bind(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        Ok(Self {
+            listener: TcpListener::bind(addr)?,
+            clients: Vec::new(),
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    pub fn clients(&self) -> usize {
+        self.clients.len()
+    }
+
+    // Block until a client connects, then send it the full current board
+    pub fn accept<const H: usize, const W: usize>(
+        &mut self,
+        engine: &Engine<H, W>,
+    ) -> io::Result<()> {
+        let (stream, _) = self.listener.accept()?;
+        stream.set_nodelay(true)?;
+
+        let mut client = BufWriter::new(stream);
+        let changes = diff(&CellArray::new(), engine.cells());
+        client.write_all(&encode(engine.generation(), &changes))?;
+        client.flush()?;
+
+        self.clients.push(client);
+        Ok(())
+    }
+
+    // Compute the next generation and send the changed cells to every client.
+    // Clients that can no longer be written to are dropped.
+    pub fn step<const H: usize, const W: usize>(&mut self, engine: &mut Engine<H, W>) {
+        engine.generate();
+
+        // The engine still holds the previous board, so nothing has to be copied
+        let cells = engine.cells();
+        let changes: Vec<_> = engine
+            .changed_since_last_generation()
+            .into_iter()
+            .map(|(x, y)| {
+                let alive = cells.cell(x as isize, y as isize).alive();
+                (x as u32, y as u32, alive)
+            })
+            .collect();
+        let data = encode(engine.generation(), &changes);
+        self.clients
+            .retain_mut(|client| client.write_all(&data).and_then(|_| client.flush()).is_ok());
+    }
+}
+
+pub struct Client {
+    stream: BufReader<TcpStream>,
+}
+
+impl Client {
+    pub fn connect(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        Ok(Self {
+            stream: BufReader::new(TcpStream::connect(addr)?),
+        })
+    }
+
+    // Block until the next update arrives and apply it to the engine.
+    // Returns Ok(false) once the server has closed the connection.
+    // Updates with more changes than the board has cells or with cells off the board
+    // fail with ErrorKind::InvalidData and leave the engine untouched.
+    pub fn apply_next<const H: usize, const W: usize>(
+        &mut self,
+        engine: &mut Engine<H, W>,
+    ) -> io::Result<bool> {
+        let mut header = [0u8; 12];
+        match self.stream.read_exact(&mut header) {
+            Ok(()) => {}
+            Err(error) if error.kind() == io::ErrorKind::UnexpectedEof => return Ok(false),
+            Err(error) => return Err(error),
+        }
+
+        let generation = u64::from_le_bytes(header[..8].try_into().unwrap());
+        let count = u32::from_le_bytes(header[8..].try_into().unwrap()) as usize;
+        if count > H * W {
+            return Err(invalid_data(format!(
+                "{} changes for a board of {} cells",
+                count,
+                H * W
+            )));
+        }
+
+        let mut data = vec![0u8; count * CHANGE_LEN];
+        self.stream.read_exact(&mut data)?;
+
+        let mut changes = Vec::with_capacity(count);
+        for change in data.chunks_exact(CHANGE_LEN) {
+            let x = u32::from_le_bytes(change[..4].try_into().unwrap()) as usize;
+            let y = u32::from_le_bytes(change[4..8].try_into().unwrap()) as usize;
+            if x >= W || y >= H {
+                return Err(invalid_data(format!(
+                    "cell ({}, {}) is off the board",
+                    x, y
+                )));
+            }
+            changes.push((x as isize, y as isize, change[8] == 1));
+        }
+
+        for (x, y, alive) in changes {
+            if alive {
+                engine.spawn_cells(&[(x, y)]);
+            } else {
+                engine.kill_cells(&[(x, y)]);
+            }
+        }
+        engine.generation = generation as usize;
+
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_loopback_sync() {
+        const H: usize = 16;
+        const W: usize = 24;
+
+        let mut engine = Engine::<H, W>::new();
+        engine.randomize_seeded(9, 0.4);
+
+        let mut server = Server::bind("127.0.0.1:0").unwrap();
+        let mut client = Client::connect(server.local_addr().unwrap()).unwrap();
+        server.accept(&engine).unwrap();
+        assert_eq!(server.clients(), 1);
+
+        let mut mirror = Engine::<H, W>::new();
+        assert!(client.apply_next(&mut mirror).unwrap());
+        assert_eq!(mirror.render_ascii(), engine.render_ascii());
+
+        for _ in 0..10 {
+            server.step(&mut engine);
+            assert!(client.apply_next(&mut mirror).unwrap());
+            assert_eq!(mirror.render_ascii(), engine.render_ascii());
+            assert_eq!(mirror.cells().fingerprint(), engine.cells().fingerprint());
+            assert_eq!(mirror.generation(), engine.generation());
+        }
+
+        drop(server);
+        assert!(!client.apply_next(&mut mirror).unwrap());
+    }
+
+    // Send raw bytes from a fake server and apply them to a 4x5 board
+    fn apply_forged(data: &[u8]) -> (io::Result<bool>, Engine<4, 5>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut client = Client::connect(listener.local_addr().unwrap()).unwrap();
+        let (mut stream, _) = listener.accept().unwrap();
+        stream.write_all(data).unwrap();
+
+        let mut engine = Engine::<4, 5>::new();
+        let result = client.apply_next(&mut engine);
+        (result, engine)
+    }
+
+    #[test]
+    fn test_forged_updates() {
+        // Claims 4 billion changes, nothing should be allocated for them
+        let mut data = 7u64.to_le_bytes().to_vec();
+        data.extend_from_slice(&u32::MAX.to_le_bytes());
+        let (result, engine) = apply_forged(&data);
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::InvalidData);
+        assert_eq!(engine.generation(), 0);
+
+        // One valid change followed by one off the board
+        let data = encode(3, &[(1, 1, true), (5, 0, true)]);
+        let (result, engine) = apply_forged(&data);
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::InvalidData);
+        assert_eq!(engine.population(), 0);
+
+        let data = encode(3, &[(1, 1, true), (4, 3, true)]);
+        let (result, engine) = apply_forged(&data);
+        assert!(result.unwrap());
+        assert_eq!(engine.population(), 2);
+        assert_eq!(engine.generation(), 3);
+    }
+}
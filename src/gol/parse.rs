@@ -0,0 +1,347 @@
+// Loaders for the text pattern formats used on the LifeWiki
+
+use std::fmt;
+
+use crate::gol::types::{CellArray, CellStorage};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    // Line and column are 1 based and count comment lines
+    UnexpectedChar {
+        line: usize,
+        column: usize,
+        found: char,
+    },
+    MissingHeader,
+    // A line that is not an "x y" pair of integers
+    InvalidCoordinates {
+        line: usize,
+    },
+    // A row longer than the board is wide
+    RowTooLong {
+        line: usize,
+        length: usize,
+    },
+    // More rows than the board is high
+    TooManyRows {
+        rows: usize,
+    },
+    // A run count above MAX_RLE_RUN, at the position of its last digit
+    RunTooLong {
+        line: usize,
+        column: usize,
+    },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::UnexpectedChar {
+                line,
+                column,
+                found,
+            } => write!(f, "unexpected '{}' at {}:{}", found, line, column),
+            ParseError::MissingHeader => write!(f, "missing format header"),
+            ParseError::InvalidCoordinates { line } => {
+                write!(f, "expected a coordinate pair on line {}", line)
+            }
+            ParseError::RowTooLong { line, length } => {
+                write!(
+                    f,
+                    "line {} has {} cells, more than fit the board",
+                    line, length
+                )
+            }
+            ParseError::TooManyRows { rows } => {
+                write!(f, "{} rows do not fit the board", rows)
+            }
+            ParseError::RunTooLong { line, column } => {
+                write!(
+                    f,
+                    "run at {}:{} is longer than {} cells",
+                    line, column, MAX_RLE_RUN
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+// Longest run a single RLE tag may have, far beyond any board
+pub const MAX_RLE_RUN: isize = 1 << 20;
+
+// Spawn a plaintext (.cells) pattern with its top left corner at (x, y),
+// see parse_plaintext. Nothing is spawned if the text is invalid.
+pub fn load_plaintext<const H: usize, const W: usize, C: CellStorage>(
+    array: &mut CellArray<H, W, C>,
+    text: &str,
+    x: isize,
+    y: isize,
+) -> Result<(), ParseError> {
+    for (dx, dy) in parse_plaintext(text)? {
+        array.spawn(x + dx, y + dy);
+    }
+    Ok(())
+}
+
+// Alive cells of a plaintext (.cells) pattern relative to its top left corner.
+// Lines starting with '!' are comments, every other line is a row
+// of '.' (dead) and 'O' (alive). Rows may have different lengths,
+// missing cells are dead.
+pub fn parse_plaintext(text: &str) -> Result<Vec<(isize, isize)>, ParseError> {
+    let mut alive = Vec::new();
+    let mut row = 0;
+
+    for (line_index, line) in text.lines().enumerate() {
+        if line.starts_with('!') {
+            continue;
+        }
+
+        for (column, c) in line.trim_end().chars().enumerate() {
+            match c {
+                'O' => alive.push((column as isize, row)),
+                '.' => {}
+                found => {
+                    return Err(ParseError::UnexpectedChar {
+                        line: line_index + 1,
+                        column: column + 1,
+                        found,
+                    })
+                }
+            }
+        }
+        row += 1;
+    }
+
+    Ok(alive)
+}
+
+// Alive cells of a run length encoded (.rle) pattern relative to its top left corner.
+// Lines starting with '#' are comments, the first other line is the
+// "x = .., y = .." header. The body is a sequence of optionally counted
+// 'b' (dead), 'o' (alive) and '$' (end of row) tags ending with '!'.
+// The header's size and rule are not checked against the body,
+// runs longer than MAX_RLE_RUN are rejected.
+pub fn parse_rle(text: &str) -> Result<Vec<(isize, isize)>, ParseError> {
+    let mut lines = text
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.starts_with('#') && !line.trim().is_empty());
+    match lines.next() {
+        Some((_, header)) if header.trim_start().starts_with('x') => {}
+        _ => return Err(ParseError::MissingHeader),
+    }
+
+    let mut alive = Vec::new();
+    let (mut x, mut y) = (0, 0);
+    let mut count: Option<isize> = None;
+
+    for (line_index, line) in lines {
+        for (column, c) in line.chars().enumerate() {
+            let run = count.unwrap_or(1);
+            match c {
+                '0'..='9' => {
+                    let digit = c as isize - '0' as isize;
+                    let run = count
+                        .unwrap_or(0)
+                        .checked_mul(10)
+                        .and_then(|run| run.checked_add(digit))
+                        .filter(|&run| run <= MAX_RLE_RUN)
+                        .ok_or(ParseError::RunTooLong {
+                            line: line_index + 1,
+                            column: column + 1,
+                        })?;
+                    count = Some(run);
+                    continue;
+                }
+                'b' => x += run,
+                'o' => {
+                    alive.extend((x..x + run).map(|x| (x, y)));
+                    x += run;
+                }
+                '$' => {
+                    x = 0;
+                    y += run;
+                }
+                '!' => return Ok(alive),
+                c if c.is_whitespace() && count.is_none() => {}
+                found => {
+                    return Err(ParseError::UnexpectedChar {
+                        line: line_index + 1,
+                        column: column + 1,
+                        found,
+                    })
+                }
+            }
+            count = None;
+        }
+    }
+
+    Ok(alive)
+}
+
+// Spawn the '#' (alive) and '.' (dead) text written by Engine::render_ascii,
+// one row per line starting at the top left corner.
+// Trailing lines with only whitespace are ignored. Unlike load_plaintext
+// the text must fit the board. Nothing is spawned if the text is invalid.
+pub fn load_ascii<const H: usize, const W: usize, C: CellStorage>(
+    array: &mut CellArray<H, W, C>,
+    text: &str,
+) -> Result<(), ParseError> {
+    let lines: Vec<&str> = text.lines().collect();
+    let rows = lines
+        .iter()
+        .rposition(|line| !line.trim().is_empty())
+        .map_or(0, |last| last + 1);
+    if rows > H {
+        return Err(ParseError::TooManyRows { rows });
+    }
+
+    let mut alive = Vec::new();
+    for (y, line) in lines[..rows].iter().enumerate() {
+        let line = line.trim_end();
+        let length = line.chars().count();
+        if length > W {
+            return Err(ParseError::RowTooLong {
+                line: y + 1,
+                length,
+            });
+        }
+
+        for (x, c) in line.chars().enumerate() {
+            match c {
+                '#' => alive.push((x as isize, y as isize)),
+                '.' => {}
+                found => {
+                    return Err(ParseError::UnexpectedChar {
+                        line: y + 1,
+                        column: x + 1,
+                        found,
+                    })
+                }
+            }
+        }
+    }
+
+    for (x, y) in alive {
+        array.spawn(x, y);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gol::patterns::spawn_glider;
+
+    const GLIDER: &str = "!Name: Glider\n\
+                          !\n\
+                          ..O\n\
+                          O.O\n\
+                          .OO\n";
+
+    #[test]
+    fn test_load_plaintext_glider() {
+        let mut loaded = CellArray::<10, 10>::new();
+        let mut spawned = CellArray::<10, 10>::new();
+        load_plaintext(&mut loaded, GLIDER, 3, 4).unwrap();
+        spawn_glider(&mut spawned, 3, 4);
+
+        assert_eq!(loaded.population(), 5);
+        assert_eq!(loaded.fingerprint(), spawned.fingerprint());
+    }
+
+    #[test]
+    fn test_load_plaintext_ragged_rows() {
+        let mut loaded = CellArray::<5, 5>::new();
+        load_plaintext(&mut loaded, "O\n.O\n\n...O", 0, 0).unwrap();
+
+        let coords: Vec<_> = loaded.live_coords().collect();
+        assert_eq!(coords, vec![(0, 0), (1, 1), (3, 3)]);
+    }
+
+    #[test]
+    fn test_load_plaintext_error() {
+        let mut loaded = CellArray::<5, 5>::new();
+        let result = load_plaintext(&mut loaded, "!comment\nO.\n.x", 0, 0);
+
+        assert_eq!(
+            result,
+            Err(ParseError::UnexpectedChar {
+                line: 3,
+                column: 2,
+                found: 'x'
+            })
+        );
+        assert_eq!(loaded.population(), 0);
+    }
+
+    #[test]
+    fn test_parse_rle() {
+        let glider = "#N Glider\n#C A comment\nx = 3, y = 3, rule = B3/S23\nbob$2bo$3o!\n";
+        assert_eq!(
+            parse_rle(glider).unwrap(),
+            vec![(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)]
+        );
+
+        // Counted row ends skip empty rows, bodies may span lines
+        assert_eq!(
+            parse_rle("x = 2, y = 4\no2$\n2o!").unwrap(),
+            vec![(0, 0), (0, 2), (1, 2)]
+        );
+
+        assert_eq!(parse_rle("bob$!"), Err(ParseError::MissingHeader));
+        assert_eq!(
+            parse_rle("x = 3, y = 1\n3q!"),
+            Err(ParseError::UnexpectedChar {
+                line: 2,
+                column: 2,
+                found: 'q'
+            })
+        );
+
+        // Runs too long to be real are rejected instead of overflowing
+        assert_eq!(
+            parse_rle("x = 1, y = 1\n1048577o!"),
+            Err(ParseError::RunTooLong { line: 2, column: 7 })
+        );
+        assert_eq!(
+            parse_rle("x = 1, y = 1\n99999999999999999999999b!"),
+            Err(ParseError::RunTooLong { line: 2, column: 7 })
+        );
+        assert_eq!(
+            parse_rle("x = 1, y = 1\n1048576$o!").unwrap(),
+            vec![(0, 1048576)]
+        );
+    }
+
+    #[test]
+    fn test_load_ascii() {
+        let mut array = CellArray::<4, 5>::new();
+        load_ascii(&mut array, ".#...\n..#\n###..\n  \n\n").unwrap();
+        assert_eq!(
+            array.live_coords().collect::<Vec<_>>(),
+            vec![(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)]
+        );
+
+        let mut array = CellArray::<2, 3>::new();
+        assert_eq!(
+            load_ascii(&mut array, "...\n####\n"),
+            Err(ParseError::RowTooLong { line: 2, length: 4 })
+        );
+        assert_eq!(
+            load_ascii(&mut array, "#\n.\n#\n"),
+            Err(ParseError::TooManyRows { rows: 3 })
+        );
+        assert_eq!(
+            load_ascii(&mut array, "#O.\n"),
+            Err(ParseError::UnexpectedChar {
+                line: 1,
+                column: 2,
+                found: 'O'
+            })
+        );
+        assert_eq!(array.population(), 0);
+    }
+}
@@ -0,0 +1,555 @@
+use std::fmt;
+
+use crate::gol::types::{CellArray, CellStorage};
+
+// A named set of cell offsets relative to the pattern's top left corner
+pub trait Pattern {
+    fn cells(&self) -> &[(isize, isize)];
+    fn name(&self) -> &str;
+}
+
+pub struct Glider;
+pub struct Blinker;
+pub struct Toad;
+pub struct Beacon;
+pub struct Block;
+pub struct Lwss;
+pub struct GliderGun;
+pub struct Eater;
+pub struct Beehive;
+pub struct Loaf;
+
+const GLIDER: [(isize, isize); 5] = [(2, 0), (2, 1), (2, 2), (1, 2), (0, 1)];
+const BLINKER: [(isize, isize); 3] = [(0, 0), (1, 0), (2, 0)];
+const TOAD: [(isize, isize); 6] = [(1, 0), (2, 0), (3, 0), (0, 1), (1, 1), (2, 1)];
+const BEACON: [(isize, isize); 6] = [(0, 0), (1, 0), (0, 1), (3, 2), (2, 3), (3, 3)];
+const BLOCK: [(isize, isize); 4] = [(0, 0), (1, 0), (0, 1), (1, 1)];
+const BEEHIVE: [(isize, isize); 6] = [(1, 0), (2, 0), (0, 1), (3, 1), (1, 2), (2, 2)];
+const LOAF: [(isize, isize); 7] = [(1, 0), (2, 0), (0, 1), (3, 1), (1, 2), (3, 2), (2, 3)];
+#[rustfmt::skip]
+const LWSS: [(isize, isize); 9] = [
+    (1, 0), (4, 0), (0, 1), (0, 2), (4, 2), (0, 3), (1, 3), (2, 3), (3, 3),
+];
+
+// Eater 1 (fishhook), absorbs gliders coming in from the top left
+const EATER: [(isize, isize); 7] = [(0, 0), (1, 0), (0, 1), (2, 1), (2, 2), (2, 3), (3, 3)];
+
+// Gosper glider gun, fires a glider towards the bottom right every 30 generations
+#[rustfmt::skip]
+const GLIDER_GUN: [(isize, isize); 36] = [
+    (24, 0), (22, 1), (24, 1), (12, 2), (13, 2), (20, 2), (21, 2), (34, 2), (35, 2),
+    (11, 3), (15, 3), (20, 3), (21, 3), (34, 3), (35, 3), (0, 4), (1, 4), (10, 4),
+    (16, 4), (20, 4), (21, 4), (0, 5), (1, 5), (10, 5), (14, 5), (16, 5), (17, 5),
+    (22, 5), (24, 5), (10, 6), (16, 6), (24, 6), (11, 7), (15, 7), (12, 8), (13, 8),
+];
+
+// Bounding box of the glider gun
+pub const GLIDER_GUN_W: usize = 36;
+pub const GLIDER_GUN_H: usize = 9;
+
+impl Pattern for Glider {
+    fn cells(&self) -> &[(isize, isize)] {
+        &GLIDER
+    }
+
+    fn name(&self) -> &str {
+        "glider"
+    }
+}
+
+// Period 2 oscillator, starts horizontal
+impl Pattern for Blinker {
+    fn cells(&self) -> &[(isize, isize)] {
+        &BLINKER
+    }
+
+    fn name(&self) -> &str {
+        "blinker"
+    }
+}
+
+// Period 2 oscillator
+impl Pattern for Toad {
+    fn cells(&self) -> &[(isize, isize)] {
+        &TOAD
+    }
+
+    fn name(&self) -> &str {
+        "toad"
+    }
+}
+
+// Period 2 oscillator, starts in its 6 cell phase
+impl Pattern for Beacon {
+    fn cells(&self) -> &[(isize, isize)] {
+        &BEACON
+    }
+
+    fn name(&self) -> &str {
+        "beacon"
+    }
+}
+
+// Still life
+impl Pattern for Block {
+    fn cells(&self) -> &[(isize, isize)] {
+        &BLOCK
+    }
+
+    fn name(&self) -> &str {
+        "block"
+    }
+}
+
+// Lightweight spaceship, travels left
+impl Pattern for Lwss {
+    fn cells(&self) -> &[(isize, isize)] {
+        &LWSS
+    }
+
+    fn name(&self) -> &str {
+        "lwss"
+    }
+}
+
+impl Pattern for GliderGun {
+    fn cells(&self) -> &[(isize, isize)] {
+        &GLIDER_GUN
+    }
+
+    fn name(&self) -> &str {
+        "glider_gun"
+    }
+}
+
+// Still life that eats the gliders of a GliderGun, see Scenario::gun_vs_eater
+impl Pattern for Eater {
+    fn cells(&self) -> &[(isize, isize)] {
+        &EATER
+    }
+
+    fn name(&self) -> &str {
+        "eater"
+    }
+}
+
+impl Pattern for Beehive {
+    fn cells(&self) -> &[(isize, isize)] {
+        &BEEHIVE
+    }
+
+    fn name(&self) -> &str {
+        "beehive"
+    }
+}
+
+impl Pattern for Loaf {
+    fn cells(&self) -> &[(isize, isize)] {
+        &LOAF
+    }
+
+    fn name(&self) -> &str {
+        "loaf"
+    }
+}
+
+// Every known pattern
+pub fn catalog() -> Vec<Box<dyn Pattern>> {
+    vec![
+        Box::new(Glider),
+        Box::new(Blinker),
+        Box::new(Toad),
+        Box::new(Beacon),
+        Box::new(Block),
+        Box::new(Lwss),
+        Box::new(GliderGun),
+        Box::new(Eater),
+        Box::new(Beehive),
+        Box::new(Loaf),
+    ]
+}
+
+// Look up a known pattern by its name, e.g. from the command line
+pub fn pattern_by_name(name: &str) -> Option<Box<dyn Pattern>> {
+    catalog().into_iter().find(|pattern| pattern.name() == name)
+}
+
+// Clockwise rotation applied by transform
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum Rotation {
+    #[default]
+    R0,
+    R90,
+    R180,
+    R270,
+}
+
+// Mirroring applied by transform. Horizontal swaps left and right,
+// Vertical swaps top and bottom.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum Flip {
+    #[default]
+    None,
+    Horizontal,
+    Vertical,
+}
+
+// Flip, then rotate the coordinates around the origin, clockwise as seen on screen.
+// The result is moved back so its bounding box starts at (0, 0) again,
+// so it can be stamped like any other pattern.
+pub fn transform(coords: &[(isize, isize)], rot: Rotation, flip: Flip) -> Vec<(isize, isize)> {
+    let transformed: Vec<(isize, isize)> = coords
+        .iter()
+        .map(|&(x, y)| match flip {
+            Flip::None => (x, y),
+            Flip::Horizontal => (-x, y),
+            Flip::Vertical => (x, -y),
+        })
+        .map(|(x, y)| match rot {
+            Rotation::R0 => (x, y),
+            Rotation::R90 => (-y, x),
+            Rotation::R180 => (-x, -y),
+            Rotation::R270 => (y, -x),
+        })
+        .collect();
+
+    let min_x = transformed.iter().map(|&(x, _)| x).min().unwrap_or(0);
+    let min_y = transformed.iter().map(|&(_, y)| y).min().unwrap_or(0);
+    transformed
+        .into_iter()
+        .map(|(x, y)| (x - min_x, y - min_y))
+        .collect()
+}
+
+// Spawn the pattern with its top left corner at (x, y), wrapping around the edges.
+// Cells are spawned through CellArray::spawn so neighbour counts stay correct.
+pub fn stamp<const H: usize, const W: usize, C: CellStorage>(
+    pattern: &dyn Pattern,
+    array: &mut CellArray<H, W, C>,
+    x: isize,
+    y: isize,
+) {
+    stamp_oriented(pattern, array, x, y, Rotation::R0, Flip::None);
+}
+
+// Why try_stamp refused to place a pattern
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PlaceError {
+    // Part of the pattern would wrap around an edge
+    OffBoard,
+    // Part of the pattern would land on an alive cell
+    Overlap,
+}
+
+impl fmt::Display for PlaceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlaceError::OffBoard => write!(f, "the pattern does not fit on the board there"),
+            PlaceError::Overlap => write!(f, "the pattern overlaps alive cells"),
+        }
+    }
+}
+
+impl std::error::Error for PlaceError {}
+
+// Same as stamp, but only if the pattern keeps its shape, see CellArray::can_place.
+// Nothing is spawned on errors.
+pub fn try_stamp<const H: usize, const W: usize, C: CellStorage>(
+    pattern: &dyn Pattern,
+    array: &mut CellArray<H, W, C>,
+    x: isize,
+    y: isize,
+) -> Result<(), PlaceError> {
+    if !array.fits(pattern.cells(), x, y) {
+        return Err(PlaceError::OffBoard);
+    }
+    if !array.can_place(pattern.cells(), x, y) {
+        return Err(PlaceError::Overlap);
+    }
+    stamp(pattern, array, x, y);
+    Ok(())
+}
+
+// Same as stamp with the pattern transformed first, see transform
+pub fn stamp_oriented<const H: usize, const W: usize, C: CellStorage>(
+    pattern: &dyn Pattern,
+    array: &mut CellArray<H, W, C>,
+    x: isize,
+    y: isize,
+    rot: Rotation,
+    flip: Flip,
+) {
+    for (dx, dy) in transform(pattern.cells(), rot, flip) {
+        array.spawn(x + dx, y + dy);
+    }
+}
+
+pub fn spawn_glider<const H: usize, const W: usize, C: CellStorage>(
+    cell_array: &mut CellArray<H, W, C>,
+    x: isize,
+    y: isize,
+) {
+    stamp(&Glider, cell_array, x, y);
+}
+
+pub fn spawn_blinker<const H: usize, const W: usize, C: CellStorage>(
+    cell_array: &mut CellArray<H, W, C>,
+    x: isize,
+    y: isize,
+) {
+    stamp(&Blinker, cell_array, x, y);
+}
+
+pub fn spawn_toad<const H: usize, const W: usize, C: CellStorage>(
+    cell_array: &mut CellArray<H, W, C>,
+    x: isize,
+    y: isize,
+) {
+    stamp(&Toad, cell_array, x, y);
+}
+
+pub fn spawn_beacon<const H: usize, const W: usize, C: CellStorage>(
+    cell_array: &mut CellArray<H, W, C>,
+    x: isize,
+    y: isize,
+) {
+    stamp(&Beacon, cell_array, x, y);
+}
+
+pub fn spawn_block<const H: usize, const W: usize, C: CellStorage>(
+    cell_array: &mut CellArray<H, W, C>,
+    x: isize,
+    y: isize,
+) {
+    stamp(&Block, cell_array, x, y);
+}
+
+pub fn spawn_lwss<const H: usize, const W: usize, C: CellStorage>(
+    cell_array: &mut CellArray<H, W, C>,
+    x: isize,
+    y: isize,
+) {
+    stamp(&Lwss, cell_array, x, y);
+}
+
+// The gun needs a board of at least GLIDER_GUN_W x GLIDER_GUN_H cells,
+// plus room for its gliders to fly. Unlike the other patterns it does not wrap:
+// cells falling outside the board are skipped.
+pub fn spawn_glider_gun<const H: usize, const W: usize, C: CellStorage>(
+    array: &mut CellArray<H, W, C>,
+    x: isize,
+    y: isize,
+) {
+    for &(dx, dy) in GliderGun.cells() {
+        let (cx, cy) = (x + dx, y + dy);
+        if (0..W as isize).contains(&cx) && (0..H as isize).contains(&cy) {
+            array.spawn(cx, cy);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gol::engine::Engine;
+
+    fn live_coords<const H: usize, const W: usize>(
+        cell_array: &CellArray<H, W>,
+    ) -> Vec<(isize, isize)> {
+        let mut coords: Vec<(isize, isize)> = cell_array.live_coords().collect();
+        coords.sort();
+        coords
+    }
+
+    #[test]
+    fn test_try_stamp() {
+        let mut array = CellArray::<10, 10>::new();
+        assert!(!array.can_place(GliderGun.cells(), 0, 0));
+        assert!(array.can_place(Glider.cells(), 0, 0));
+
+        assert_eq!(
+            try_stamp(&GliderGun, &mut array, 0, 0),
+            Err(PlaceError::OffBoard)
+        );
+        assert_eq!(array.population(), 0);
+
+        assert_eq!(try_stamp(&Glider, &mut array, 0, 0), Ok(()));
+        assert_eq!(array.population(), 5);
+        assert_eq!(
+            try_stamp(&Block, &mut array, 1, 1),
+            Err(PlaceError::Overlap)
+        );
+        assert_eq!(try_stamp(&Block, &mut array, 4, 4), Ok(()));
+        assert_eq!(array.population(), 9);
+    }
+
+    #[test]
+    fn test_pattern_by_name() {
+        let mut stamped = CellArray::<10, 10>::new();
+        let glider = pattern_by_name("glider").unwrap();
+        assert_eq!(glider.name(), "glider");
+        stamp(glider.as_ref(), &mut stamped, 0, 0);
+
+        let mut spawned = CellArray::<10, 10>::new();
+        spawn_glider(&mut spawned, 0, 0);
+        assert_eq!(live_coords(&stamped), live_coords(&spawned));
+        assert_eq!(stamped.fingerprint(), spawned.fingerprint());
+
+        assert!(pattern_by_name("unknown").is_none());
+        for pattern in catalog() {
+            assert!(pattern_by_name(pattern.name()).is_some());
+        }
+    }
+
+    #[test]
+    fn test_transform() {
+        // The glider flies towards the bottom right, rotated by 90 degrees
+        // it flies towards the bottom left:
+        //  .O.
+        //  O..
+        //  OOO
+        let mut rotated = transform(Glider.cells(), Rotation::R90, Flip::None);
+        rotated.sort();
+        assert_eq!(rotated, vec![(0, 1), (0, 2), (1, 0), (1, 2), (2, 2)]);
+
+        // Mirroring twice is a half turn
+        let mut flipped = transform(
+            &transform(Glider.cells(), Rotation::R0, Flip::Horizontal),
+            Rotation::R0,
+            Flip::Vertical,
+        );
+        let mut turned = transform(Glider.cells(), Rotation::R180, Flip::None);
+        flipped.sort();
+        turned.sort();
+        assert_eq!(flipped, turned);
+
+        let mut identity = transform(Lwss.cells(), Rotation::R0, Flip::None);
+        identity.sort();
+        let mut lwss = Lwss.cells().to_vec();
+        lwss.sort();
+        assert_eq!(identity, lwss);
+    }
+
+    #[test]
+    fn test_gliders_in_every_direction() {
+        let directions = [
+            (Rotation::R0, (1, 1)),
+            (Rotation::R90, (-1, 1)),
+            (Rotation::R180, (-1, -1)),
+            (Rotation::R270, (1, -1)),
+        ];
+        for (rot, (dx, dy)) in directions {
+            let mut engine = Engine::<12, 12>::new();
+            stamp_oriented(&Glider, &mut engine.cells, 5, 5, rot, Flip::None);
+            let start = live_coords(engine.cells());
+            engine.step_n(4);
+
+            let moved: Vec<_> = start.iter().map(|&(x, y)| (x + dx, y + dy)).collect();
+            assert_eq!(live_coords(engine.cells()), moved, "{:?}", rot);
+        }
+    }
+
+    #[test]
+    fn test_glider() {
+        let mut cell_array = CellArray::<10, 10>::new();
+        spawn_glider(&mut cell_array, 3, 4);
+        assert_eq!(
+            live_coords(&cell_array),
+            vec![(3, 5), (4, 6), (5, 4), (5, 5), (5, 6)]
+        );
+    }
+
+    #[test]
+    fn test_blinker() {
+        let mut engine = Engine::<10, 10>::new();
+        spawn_blinker(&mut engine.cells, 3, 4);
+        let start = live_coords(engine.cells());
+        assert_eq!(start, vec![(3, 4), (4, 4), (5, 4)]);
+
+        engine.generate();
+        assert_eq!(live_coords(engine.cells()), vec![(4, 3), (4, 4), (4, 5)]);
+
+        engine.generate();
+        assert_eq!(live_coords(engine.cells()), start);
+    }
+
+    #[test]
+    fn test_toad() {
+        let mut cell_array = CellArray::<10, 10>::new();
+        spawn_toad(&mut cell_array, 1, 1);
+        assert_eq!(
+            live_coords(&cell_array),
+            vec![(1, 2), (2, 1), (2, 2), (3, 1), (3, 2), (4, 1)]
+        );
+    }
+
+    #[test]
+    fn test_beacon() {
+        let mut cell_array = CellArray::<10, 10>::new();
+        spawn_beacon(&mut cell_array, 1, 1);
+        assert_eq!(
+            live_coords(&cell_array),
+            vec![(1, 1), (1, 2), (2, 1), (3, 4), (4, 3), (4, 4)]
+        );
+    }
+
+    #[test]
+    fn test_block() {
+        let mut cell_array = CellArray::<10, 10>::new();
+        spawn_block(&mut cell_array, 8, 8);
+        assert_eq!(
+            live_coords(&cell_array),
+            vec![(8, 8), (8, 9), (9, 8), (9, 9)]
+        );
+        assert_eq!(cell_array.cell(8, 8).neighbours(), 3);
+    }
+
+    #[test]
+    fn test_glider_gun_fires() {
+        let mut engine = Engine::<64, 64>::new();
+        spawn_glider_gun(&mut engine.cells, 1, 1);
+        let initial = engine.population();
+        assert_eq!(initial, GLIDER_GUN.len());
+
+        for _ in 0..30 {
+            engine.generate();
+        }
+        assert!(engine.population() > initial);
+
+        // A static pattern would repeat the same board every generation
+        let result = engine.generate_until_stable(30, 30);
+        assert!(!result.stabilized);
+    }
+
+    #[test]
+    fn test_glider_gun_clamped() {
+        // Too narrow: the right-most cells are dropped instead of wrapping
+        let mut cell_array = CellArray::<10, 30>::new();
+        spawn_glider_gun(&mut cell_array, 0, 0);
+        assert_eq!(
+            cell_array.population(),
+            GLIDER_GUN.iter().filter(|&&(x, _)| x < 30).count()
+        );
+        assert!(cell_array.live_coords().all(|(x, _)| x < 30));
+    }
+
+    #[test]
+    fn test_lwss() {
+        let mut cell_array = CellArray::<10, 10>::new();
+        spawn_lwss(&mut cell_array, 2, 2);
+        assert_eq!(
+            live_coords(&cell_array),
+            vec![
+                (2, 3),
+                (2, 4),
+                (2, 5),
+                (3, 2),
+                (3, 5),
+                (4, 5),
+                (5, 5),
+                (6, 2),
+                (6, 4)
+            ]
+        );
+    }
+}
@@ -0,0 +1,146 @@
+use crate::gol::engine::Engine;
+use crate::gol::patterns::{Eater, Glider, GliderGun, Pattern};
+
+// A pattern placed with the top left corner of its bounding box at (x, y),
+// optionally mirrored inside that box
+struct Placement {
+    pattern: Box<dyn Pattern>,
+    x: isize,
+    y: isize,
+    flip_x: bool,
+    flip_y: bool,
+}
+
+// Several patterns put together on one board, e.g. for demos.
+// Positions are clamped so every pattern fits on the board,
+// a board too small for a preset changes how it plays out.
+//
+//  let engine = Scenario::new()
+//      .place(Glider, 0, 0)
+//      .place_flipped(Glider, 10, 1, true, false)
+//      .build::<30, 30>();
+#[derive(Default)]
+pub struct Scenario {
+    placements: Vec<Placement>,
+}
+
+impl Scenario {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn place(self, pattern: impl Pattern + 'static, x: isize, y: isize) -> Self {
+        self.place_flipped(pattern, x, y, false, false)
+    }
+
+    // flip_x mirrors left and right, flip_y top and bottom
+    pub fn place_flipped(
+        mut self,
+        pattern: impl Pattern + 'static,
+        x: isize,
+        y: isize,
+        flip_x: bool,
+        flip_y: bool,
+    ) -> Self {
+        self.placements.push(Placement {
+            pattern: Box::new(pattern),
+            x,
+            y,
+            flip_x,
+            flip_y,
+        });
+        self
+    }
+
+    // Two gliders flying towards each other that annihilate
+    // after 14 generations, leaving an empty board. Needs 15x12 cells.
+    pub fn two_glider_collision() -> Self {
+        Self::new()
+            .place(Glider, 0, 0)
+            .place_flipped(Glider, 10, 1, true, false)
+    }
+
+    // A Gosper glider gun whose gliders are all eaten,
+    // so the board repeats every 30 generations. Needs 56x42 cells.
+    pub fn gun_vs_eater() -> Self {
+        Self::new().place(GliderGun, 0, 0).place(Eater, 52, 38)
+    }
+
+    // Spawn every pattern onto the current board of the engine
+    pub fn spawn_into<const H: usize, const W: usize>(&self, engine: &mut Engine<H, W>) {
+        for placement in &self.placements {
+            let cells = placement.pattern.cells();
+            let width = cells.iter().map(|&(x, _)| x + 1).max().unwrap_or(0);
+            let height = cells.iter().map(|&(_, y)| y + 1).max().unwrap_or(0);
+            let x = placement.x.clamp(0, (W as isize - width).max(0));
+            let y = placement.y.clamp(0, (H as isize - height).max(0));
+
+            let coords: Vec<_> = cells
+                .iter()
+                .map(|&(dx, dy)| {
+                    let dx = if placement.flip_x { width - 1 - dx } else { dx };
+                    let dy = if placement.flip_y {
+                        height - 1 - dy
+                    } else {
+                        dy
+                    };
+                    (x + dx, y + dy)
+                })
+                .collect();
+            engine.spawn_cells(&coords);
+        }
+    }
+
+    // A fresh engine with the scenario as its start board
+    pub fn build<const H: usize, const W: usize>(&self) -> Engine<H, W> {
+        let mut engine = Engine::new();
+        self.spawn_into(&mut engine);
+        engine.mark_start();
+        engine
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gol::patterns::Block;
+
+    #[test]
+    fn test_two_glider_collision() {
+        let mut engine = Scenario::two_glider_collision().build::<30, 30>();
+        assert_eq!(engine.population(), 10);
+
+        assert_eq!(engine.generate_until_extinct(100), Some(14));
+    }
+
+    #[test]
+    fn test_gun_vs_eater() {
+        let mut engine = Scenario::gun_vs_eater().build::<64, 64>();
+        engine.step_n(600);
+        let settled = *engine.cells();
+        engine.step_n(30);
+        assert_eq!(*engine.cells(), settled);
+
+        // Nothing got past the eater
+        let (_, _, max_x, max_y) = engine.cells().bounding_box().unwrap();
+        assert!(max_x < 56 && max_y < 42, "({}, {})", max_x, max_y);
+    }
+
+    #[test]
+    fn test_placement() {
+        // Clamped into the top right corner
+        let engine = Scenario::new().place(Block, 100, -5).build::<6, 8>();
+        assert_eq!(
+            engine.cells().live_coords().collect::<Vec<_>>(),
+            vec![(6, 0), (7, 0), (6, 1), (7, 1)]
+        );
+
+        let engine = Scenario::new()
+            .place_flipped(Glider, 1, 1, true, true)
+            .build::<6, 6>();
+        assert_eq!(
+            engine.cells().live_coords().collect::<Vec<_>>(),
+            vec![(1, 1), (2, 1), (1, 2), (3, 2), (1, 3)]
+        );
+    }
+}
@@ -0,0 +1,561 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::gol::engine::Engine;
+use crate::gol::parse::{self, ParseError};
+use crate::gol::types::CellArray;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+// Binary layout (integers are little endian):
+//  [magic: 4] [H: u32] [W: u32] [generation: u64] [cells: H * W bytes]
+// Every cell is stored as its raw byte, row by row.
+const MAGIC: [u8; 4] = *b"GOL1";
+const HEADER_LEN: usize = 4 + 4 + 4 + 8;
+
+// Life 1.06 is a header line followed by one "x y" line per alive cell,
+// lines starting with '#' after the header (#D, #N, ...) are comments
+const LIFE106_HEADER: &str = "#Life 1.06";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LoadError {
+    InvalidMagic,
+    Truncated,
+    // Dimensions are (H, W)
+    DimensionMismatch {
+        expected: (usize, usize),
+        found: (usize, usize),
+    },
+    // The file could not be read
+    Io(io::ErrorKind),
+    // load_file does not know the format of files with this extension
+    UnknownExtension(String),
+    Parse(ParseError),
+    // A cell at (x, y) that lies outside the board
+    OutOfBounds {
+        x: usize,
+        y: usize,
+    },
+    // Malformed JSON, with serde_json's description of the problem
+    #[cfg(feature = "serde")]
+    Json(String),
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::InvalidMagic => write!(f, "not a saved board"),
+            LoadError::Truncated => write!(f, "saved board is truncated"),
+            LoadError::DimensionMismatch { expected, found } => write!(
+                f,
+                "board is {}x{} but the engine is {}x{}",
+                found.0, found.1, expected.0, expected.1
+            ),
+            LoadError::Io(kind) => write!(f, "could not read the file: {}", kind),
+            LoadError::UnknownExtension(extension) => {
+                write!(f, "unknown pattern file extension '{}'", extension)
+            }
+            LoadError::Parse(err) => write!(f, "invalid pattern: {}", err),
+            LoadError::OutOfBounds { x, y } => write!(f, "cell ({}, {}) is off the board", x, y),
+            #[cfg(feature = "serde")]
+            LoadError::Json(err) => write!(f, "invalid json: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+impl From<ParseError> for LoadError {
+    fn from(err: ParseError) -> Self {
+        LoadError::Parse(err)
+    }
+}
+
+// Board as written by Engine::to_json, the alive cells are listed
+// as (x, y) row by row, so sparse boards stay small
+#[cfg(feature = "serde")]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BoardState {
+    pub width: usize,
+    pub height: usize,
+    pub generation: usize,
+    pub live_cells: Vec<(usize, usize)>,
+}
+
+// Cells of a Life 1.06 text as they are written, before any wrapping
+fn parse_life106(text: &str) -> Result<Vec<(isize, isize)>, ParseError> {
+    let mut lines = text
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty());
+    match lines.next() {
+        Some((_, header)) if header.trim() == LIFE106_HEADER => {}
+        _ => return Err(ParseError::MissingHeader),
+    }
+
+    let mut coords = Vec::new();
+    for (index, line) in lines {
+        if line.trim_start().starts_with('#') {
+            continue;
+        }
+        let invalid = || ParseError::InvalidCoordinates { line: index + 1 };
+
+        let mut parts = line.split_whitespace();
+        let x = parts
+            .next()
+            .and_then(|x| x.parse().ok())
+            .ok_or_else(invalid)?;
+        let y = parts
+            .next()
+            .and_then(|y| y.parse().ok())
+            .ok_or_else(invalid)?;
+        if parts.next().is_some() {
+            return Err(invalid());
+        }
+        coords.push((x, y));
+    }
+    Ok(coords)
+}
+
+impl<const H: usize, const W: usize> Engine<H, W> {
+    pub fn save_to_bytes(&self) -> Vec<u8> {
+        let mut data = Vec::with_capacity(HEADER_LEN + H * W);
+        data.extend_from_slice(&MAGIC);
+        data.extend_from_slice(&(H as u32).to_le_bytes());
+        data.extend_from_slice(&(W as u32).to_le_bytes());
+        data.extend_from_slice(&(self.generation as u64).to_le_bytes());
+
+        for y in 0..H {
+            for x in 0..W {
+                data.push(self.cells.cell(x as isize, y as isize).raw());
+            }
+        }
+
+        data
+    }
+
+    // Replace the board with one produced by save_to_bytes and start a new run
+    // from it at the saved generation, see Engine::reset_run.
+    // Only the alive bit of each stored cell is used,
+    // the neighbour counts are rebuilt by spawning the cells.
+    pub fn load_from_bytes(&mut self, data: &[u8]) -> Result<(), LoadError> {
+        if data.len() < MAGIC.len() || data[..MAGIC.len()] != MAGIC {
+            return Err(LoadError::InvalidMagic);
+        }
+        if data.len() < HEADER_LEN {
+            return Err(LoadError::Truncated);
+        }
+
+        let read_u32 = |at: usize| u32::from_le_bytes(data[at..at + 4].try_into().unwrap());
+        let height = read_u32(4) as usize;
+        let width = read_u32(8) as usize;
+        let generation = u64::from_le_bytes(data[12..20].try_into().unwrap());
+
+        if (height, width) != (H, W) {
+            return Err(LoadError::DimensionMismatch {
+                expected: (H, W),
+                found: (height, width),
+            });
+        }
+
+        let payload = &data[HEADER_LEN..];
+        if payload.len() < H * W {
+            return Err(LoadError::Truncated);
+        }
+
+        self.cells.reset();
+        for (index, byte) in payload[..H * W].iter().enumerate() {
+            if byte & 1 == 1 {
+                self.cells.spawn((index % W) as isize, (index / W) as isize);
+            }
+        }
+        self.reset_run();
+        self.mark_start();
+        self.generation = generation as usize;
+
+        Ok(())
+    }
+
+    // The board and generation as plain data, what to_json writes
+    #[cfg(feature = "serde")]
+    pub fn board_state(&self) -> BoardState {
+        BoardState {
+            width: W,
+            height: H,
+            generation: self.generation,
+            live_cells: self
+                .cells
+                .live_coords()
+                .map(|(x, y)| (x as usize, y as usize))
+                .collect(),
+        }
+    }
+
+    // The board and generation as a BoardState in JSON
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(&self.board_state()).expect("a BoardState is always valid json")
+    }
+
+    // Replace the board with one written by to_json and start a new run from it
+    // at the saved generation, like load_from_bytes.
+    // The board is left untouched if the JSON is invalid or does not fit the board.
+    #[cfg(feature = "serde")]
+    pub fn from_json(&mut self, json: &str) -> Result<(), LoadError> {
+        let state: BoardState =
+            serde_json::from_str(json).map_err(|err| LoadError::Json(err.to_string()))?;
+
+        if (state.height, state.width) != (H, W) {
+            return Err(LoadError::DimensionMismatch {
+                expected: (H, W),
+                found: (state.height, state.width),
+            });
+        }
+        if let Some(&(x, y)) = state.live_cells.iter().find(|&&(x, y)| x >= W || y >= H) {
+            return Err(LoadError::OutOfBounds { x, y });
+        }
+
+        self.cells.reset();
+        for (x, y) in state.live_cells {
+            self.cells.spawn(x as isize, y as isize);
+        }
+        self.reset_run();
+        self.mark_start();
+        self.generation = state.generation;
+        Ok(())
+    }
+
+    // Alive cells in the Life 1.06 format, relative to the top left corner
+    pub fn to_life106(&self) -> String {
+        let mut text = String::from(LIFE106_HEADER);
+        text.push('\n');
+        for (x, y) in self.cells.live_coords() {
+            text.push_str(&format!("{} {}\n", x, y));
+        }
+        text
+    }
+
+    // Replace the board with the cells of a Life 1.06 text and start a new run from it.
+    // Coordinates outside the board wrap around the edges.
+    // The board is left untouched if the text is invalid.
+    pub fn from_life106(&mut self, text: &str) -> Result<(), ParseError> {
+        let coords = parse_life106(text)?;
+
+        self.cells.reset();
+        for (x, y) in coords {
+            self.cells.spawn(x, y);
+        }
+        self.reset_run();
+        self.mark_start();
+        Ok(())
+    }
+
+    // Replace the board with the pattern in a file, centered on the board,
+    // and start a new run from it, see Engine::reset_run. It becomes the board restart() returns to.
+    // The format follows the extension: .rle, .cells or .lif / .life for Life 1.06.
+    // The board is left untouched if the file can not be read or parsed.
+    pub fn load_file(&mut self, path: &Path) -> Result<(), LoadError> {
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .unwrap_or("")
+            .to_ascii_lowercase();
+        let parse = match extension.as_str() {
+            "rle" => parse::parse_rle,
+            "cells" => parse::parse_plaintext,
+            "lif" | "life" => parse_life106,
+            _ => return Err(LoadError::UnknownExtension(extension)),
+        };
+
+        let text = fs::read_to_string(path).map_err(|err| LoadError::Io(err.kind()))?;
+        let coords = parse(&text)?;
+
+        self.cells.reset();
+        self.cells.stamp_centered(&coords);
+        self.reset_run();
+        self.mark_start();
+        Ok(())
+    }
+
+    // Replace the board with the text written by render_ascii and start a new run
+    // from it, see parse::load_ascii. The board is left as is on errors.
+    pub fn from_ascii(&mut self, text: &str) -> Result<(), ParseError> {
+        let mut cells = CellArray::with_topology(self.cells.topology());
+        cells.set_neighbourhood(self.cells.neighbourhood());
+        parse::load_ascii(&mut cells, text)?;
+        *self.cells = cells;
+        self.reset_run();
+        self.mark_start();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bytes_round_trip() {
+        let mut engine = Engine::<12, 16>::new();
+        engine.randomize_seeded(11, 0.4);
+        for _ in 0..3 {
+            engine.generate();
+        }
+
+        let data = engine.save_to_bytes();
+        assert_eq!(data.len(), HEADER_LEN + 12 * 16);
+
+        let mut loaded = Engine::<12, 16>::new();
+        loaded.load_from_bytes(&data).unwrap();
+        assert_eq!(loaded.generation(), 3);
+        assert_eq!(loaded.population(), engine.population());
+        assert_eq!(loaded.render_ascii(), engine.render_ascii());
+        assert_eq!(loaded.cells().fingerprint(), engine.cells().fingerprint());
+    }
+
+    #[test]
+    fn test_loaders_start_new_run() {
+        let mut saved = Engine::<8, 8>::new();
+        saved.spawn_cells(&[(1, 2), (2, 2), (3, 2)]);
+        let bytes = saved.save_to_bytes();
+        let life106 = saved.to_life106();
+        let ascii = saved.render_ascii();
+
+        let loaders: [&dyn Fn(&mut Engine<8, 8>); 3] = [
+            &|engine| engine.load_from_bytes(&bytes).unwrap(),
+            &|engine| engine.from_life106(&life106).unwrap(),
+            &|engine| engine.from_ascii(&ascii).unwrap(),
+        ];
+        for load in loaders {
+            let mut engine = Engine::<8, 8>::new();
+            engine.set_undo_capacity(4);
+            engine.randomize_seeded(5, 0.5);
+            engine.step_n(3);
+            load(&mut engine);
+
+            assert_eq!(engine.generation(), 0);
+            assert_eq!(*engine.cells(), *saved.cells());
+            assert!(engine.history().is_empty());
+            assert_eq!(engine.population_delta(), 0);
+            assert!(engine.changed_since_last_generation().is_empty());
+            assert!(!engine.undo());
+
+            // The loaded board is the one restart returns to
+            engine.step_n(3);
+            engine.restart();
+            assert_eq!(*engine.cells(), *saved.cells());
+        }
+    }
+
+    #[test]
+    fn test_life106_round_trip() {
+        let mut engine = Engine::<20, 30>::new();
+        engine.randomize_seeded(5, 0.3);
+
+        let text = engine.to_life106();
+        assert!(text.starts_with("#Life 1.06\n"));
+        assert_eq!(text.lines().count(), engine.population() + 1);
+
+        let mut loaded = Engine::<20, 30>::new();
+        loaded.from_life106(&text).unwrap();
+        assert!(loaded
+            .cells()
+            .live_coords()
+            .eq(engine.cells().live_coords()));
+    }
+
+    #[test]
+    fn test_ascii_blinker() {
+        let mut engine = Engine::<5, 5>::new();
+        engine.from_ascii(".....\n.###.\n.....\n").unwrap();
+        engine.generate();
+        assert_eq!(
+            engine.render_ascii(),
+            "..#..\n\
+             ..#..\n\
+             ..#..\n\
+             .....\n\
+             .....\n"
+        );
+    }
+
+    #[test]
+    fn test_ascii_round_trip() {
+        let mut engine = Engine::<9, 13>::new();
+        engine.randomize_seeded(8, 0.5);
+        let text = engine.render_ascii();
+
+        let mut loaded = Engine::<9, 13>::new();
+        loaded.spawn_cells(&[(0, 0)]);
+        loaded.from_ascii(&text).unwrap();
+        assert_eq!(loaded.cells(), engine.cells());
+        assert_eq!(loaded.render_ascii(), text);
+
+        // Too wide, the board is kept
+        assert!(loaded.from_ascii(&"#".repeat(14)).is_err());
+        assert_eq!(loaded.render_ascii(), text);
+    }
+
+    #[test]
+    fn test_load_file() {
+        let dir = std::env::temp_dir().join(format!("gol-load-file-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let rle = dir.join("glider.rle");
+        fs::write(
+            &rle,
+            "#N Glider\nx = 3, y = 3, rule = B3/S23\nbob$2bo$3o!\n",
+        )
+        .unwrap();
+
+        let mut engine = Engine::<9, 9>::new();
+        engine.load_file(&rle).unwrap();
+        assert_eq!(
+            engine.cells().live_coords().collect::<Vec<_>>(),
+            vec![(4, 3), (5, 4), (3, 5), (4, 5), (5, 5)]
+        );
+
+        // Loading starts a new run from the loaded pattern
+        engine.set_undo_capacity(4);
+        engine.step_n(3);
+        let cells = dir.join("blinker.CELLS");
+        fs::write(&cells, "!Name: Blinker\nOOO\n").unwrap();
+        engine.load_file(&cells).unwrap();
+        assert_eq!(
+            engine.cells().live_coords().collect::<Vec<_>>(),
+            vec![(3, 4), (4, 4), (5, 4)]
+        );
+        assert_eq!(engine.generation(), 0);
+        assert!(engine.history().is_empty());
+        assert!(engine.changed_since_last_generation().is_empty());
+        assert!(!engine.undo());
+        engine.step_n(3);
+        engine.restart();
+        assert_eq!(
+            engine.cells().live_coords().collect::<Vec<_>>(),
+            vec![(3, 4), (4, 4), (5, 4)]
+        );
+
+        let unknown = dir.join("glider.txt");
+        assert_eq!(
+            engine.load_file(&unknown),
+            Err(LoadError::UnknownExtension("txt".to_string()))
+        );
+        assert_eq!(
+            engine.load_file(&dir.join("missing.life")),
+            Err(LoadError::Io(io::ErrorKind::NotFound))
+        );
+        fs::write(&rle, "bob$!").unwrap();
+        assert_eq!(
+            engine.load_file(&rle),
+            Err(LoadError::Parse(ParseError::MissingHeader))
+        );
+        assert_eq!(engine.population(), 3);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_life106_errors() {
+        let mut engine = Engine::<5, 5>::new();
+        assert_eq!(engine.from_life106("0 0\n"), Err(ParseError::MissingHeader));
+        assert_eq!(
+            engine.from_life106("#Life 1.06\n0 0\n1 x\n"),
+            Err(ParseError::InvalidCoordinates { line: 3 })
+        );
+        assert_eq!(engine.population(), 0);
+
+        // Negative coordinates wrap
+        engine.from_life106("#Life 1.06\n-1 -1\n").unwrap();
+        assert!(engine.cells().cell(4, 4).alive());
+
+        // Description and name lines are skipped
+        engine
+            .from_life106("#Life 1.06\n#N Blinker\n#D A period 2 oscillator\n1 2\n2 2\n#D\n3 2\n")
+            .unwrap();
+        assert_eq!(
+            engine.cells().live_coords().collect::<Vec<_>>(),
+            vec![(1, 2), (2, 2), (3, 2)]
+        );
+    }
+
+    #[test]
+    fn test_load_errors() {
+        let data = Engine::<12, 16>::new().save_to_bytes();
+
+        let mut other = Engine::<16, 12>::new();
+        assert_eq!(
+            other.load_from_bytes(&data),
+            Err(LoadError::DimensionMismatch {
+                expected: (16, 12),
+                found: (12, 16)
+            })
+        );
+
+        let mut engine = Engine::<12, 16>::new();
+        assert_eq!(
+            engine.load_from_bytes(b"not a board"),
+            Err(LoadError::InvalidMagic)
+        );
+        assert_eq!(
+            engine.load_from_bytes(&data[..data.len() - 1]),
+            Err(LoadError::Truncated)
+        );
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod json_tests {
+    use super::*;
+
+    #[test]
+    fn test_json_round_trip() {
+        let mut engine = Engine::<12, 16>::new();
+        engine.randomize_seeded(9, 0.3);
+        engine.step_n(3);
+
+        let json = engine.to_json();
+        let mut loaded = Engine::<12, 16>::new();
+        loaded.step_n(2);
+        loaded.from_json(&json).unwrap();
+
+        assert_eq!(
+            loaded.cells().live_coords().collect::<Vec<_>>(),
+            engine.cells().live_coords().collect::<Vec<_>>()
+        );
+        assert_eq!(loaded.generation(), 3);
+        assert!(loaded.history().is_empty());
+        assert!(loaded.cells().verify_neighbour_counts());
+    }
+
+    #[test]
+    fn test_from_json_errors() {
+        let mut engine = Engine::<4, 5>::new();
+        engine.spawn_cells(&[(1, 1)]);
+
+        let json = r#"{"width":5,"height":4,"generation":2,"live_cells":[[0,0],[4,3]]}"#;
+        engine.from_json(json).unwrap();
+        assert_eq!(
+            engine.cells().live_coords().collect::<Vec<_>>(),
+            vec![(0, 0), (4, 3)]
+        );
+
+        let json = r#"{"width":6,"height":4,"generation":0,"live_cells":[]}"#;
+        assert_eq!(
+            engine.from_json(json),
+            Err(LoadError::DimensionMismatch {
+                expected: (4, 5),
+                found: (4, 6)
+            })
+        );
+        let json = r#"{"width":5,"height":4,"generation":0,"live_cells":[[5,0]]}"#;
+        assert_eq!(
+            engine.from_json(json),
+            Err(LoadError::OutOfBounds { x: 5, y: 0 })
+        );
+        assert!(matches!(engine.from_json("{"), Err(LoadError::Json(_))));
+        assert_eq!(engine.population(), 2);
+    }
+}
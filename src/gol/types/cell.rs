@@ -1,17 +1,22 @@
-use std::fmt;
+use core::fmt;
 
 // Wrapper around a u8.
 // Represents the state of a cell.
 // Offers simple API for manipulating the state via bitwise operations.
 // The first bit is the state of the cell (0 = dead, 1 = alive)
 // The next 4 bits are the number of neighbors in binary
-// The last 3 bits are unused
-//  [x, x, x, |0, 0, 0, 0, |1] -> Alive cell with 0 neighbors
-//  [x, x, x, |1, 0, 0, 0, |0] -> Dead cell with 8 neighbors
-#[derive(Debug, Copy, Clone)]
+// The last 3 bits count down the generations a dying cell has left,
+// only used by rules with more than 2 states (see Rule)
+//  [0, 0, 0, |0, 0, 0, 0, |1] -> Alive cell with 0 neighbors
+//  [0, 0, 0, |1, 0, 0, 0, |0] -> Dead cell with 8 neighbors
+//  [0, 1, 0, |0, 0, 1, 1, |0] -> Cell with 2 generations of dying left and 3 neighbors
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub struct Cell(u8);
 
 impl Cell {
+    // Largest count the 4 neighbour bits are used for, a Moore neighbourhood's worth
+    pub const MAX_NEIGHBOURS: u8 = 8;
+
     pub fn new() -> Cell {
         Cell(0)
     }
@@ -26,6 +31,25 @@ impl Cell {
         self.0 &= !1;
     }
 
+    // Bitwise operation to flip the first bit
+    pub fn toggle(&mut self) {
+        self.0 ^= 1;
+    }
+
+    // The full state byte
+    pub fn raw(&self) -> u8 {
+        self.0
+    }
+
+    // Cell from a full state byte, a neighbour count above MAX_NEIGHBOURS is clamped
+    pub fn from_raw(byte: u8) -> Cell {
+        let mut cell = Cell(byte);
+        if cell.neighbours() > Cell::MAX_NEIGHBOURS {
+            cell.set_neighbours(Cell::MAX_NEIGHBOURS);
+        }
+        cell
+    }
+
     // Bitwise operation to check if the first bit is 1
     pub fn alive(&self) -> bool {
         self.0 & 1 == 1
@@ -36,22 +60,53 @@ impl Cell {
         (self.0 >> 1) & 0b0000_1111
     }
 
-    // Bitwise operation to increment the number of neighbors
+    // Bitwise operation to overwrite the number of neighbors
+    pub fn set_neighbours(&mut self, count: u8) {
+        assert!(
+            count <= Cell::MAX_NEIGHBOURS,
+            "Neighbor count must be between 0 and {}",
+            Cell::MAX_NEIGHBOURS
+        );
+        self.0 = (self.0 & !0b0001_1110) | (count << 1);
+    }
+
+    // Bitwise operation to get the dying countdown, 0 for alive and dead cells
+    pub fn dying(&self) -> u8 {
+        self.0 >> 5
+    }
+
+    // Bitwise operation to overwrite the dying countdown
+    pub fn set_dying(&mut self, countdown: u8) {
+        assert!(countdown <= 7, "Dying countdown must be between 0 and 7");
+        self.0 = (self.0 & 0b0001_1111) | (countdown << 5);
+    }
+
+    // Bitwise operation to increment the number of neighbors.
+    // Going past MAX_NEIGHBOURS is a bookkeeping bug: it panics in debug builds
+    // and saturates in release builds, so the count never spills into the unused bits.
     pub fn add_neighbour(&mut self) {
-        let count = (self.0 >> 1) & 0b1111;
-        assert!(count + 1 <= 8, "Neighbor count must be between 0 and 8");
-        self.0 = (self.0 & 0b0000_0001) | ((count + 1) << 1);
+        let count = self.neighbours();
+        debug_assert!(count < Cell::MAX_NEIGHBOURS, "neighbour count overflow");
+        if count < Cell::MAX_NEIGHBOURS {
+            self.set_neighbours(count + 1);
+        }
     }
 
-    // Bitwise operation to decrement the number of neighbors
+    // Bitwise operation to decrement the number of neighbors.
+    // Going below 0 panics in debug builds and saturates in release builds
+    // instead of wrapping around to 15.
     pub fn remove_neighbour(&mut self) {
-        let count = (self.0 >> 1) & 0b1111;
-        // if count == 0 {
-        //     return;
-        // }
-        // TODO: This part of the code does not behave as intended.
-        // assert!(count >= 0, "Neighbor count must be between 0 and 8");
-        self.0 = (self.0 & 0b0000_0001) | ((count - 1) << 1);
+        let count = self.neighbours();
+        debug_assert!(count > 0, "neighbour count underflow");
+        if count > 0 {
+            self.set_neighbours(count - 1);
+        }
+    }
+}
+
+impl Default for Cell {
+    fn default() -> Self {
+        Cell::new()
     }
 }
 
@@ -116,6 +171,18 @@ mod test_cell {
         assert_eq!(cell == 0b00000110, true);
     }
 
+    #[test]
+    fn test_set_neighbours() {
+        let mut cell = Cell::new();
+        cell.spawn();
+        cell.set_neighbours(5);
+        assert_eq!(cell.neighbours(), 5);
+        assert!(cell.alive());
+        assert_eq!(cell.to_string(), "00001011");
+        cell.set_neighbours(0);
+        assert!(cell == 0b00000001);
+    }
+
     #[test]
     fn test_decrement_neighbours() {
         let mut cell = Cell::new();
@@ -128,4 +195,98 @@ mod test_cell {
         assert_eq!(cell.to_string(), "00000000");
         assert_eq!(cell == 0b00000000, true);
     }
+
+    #[test]
+    fn test_toggle() {
+        let mut cell = Cell::new();
+        cell.add_neighbour();
+        cell.toggle();
+        assert!(cell == 0b00000011);
+        cell.toggle();
+        assert!(cell == 0b00000010);
+    }
+
+    #[test]
+    fn test_size() {
+        // A plain Copy byte, so a board is a flat array of bytes
+        assert_eq!(std::mem::size_of::<Cell>(), 1);
+        assert_eq!(std::mem::size_of::<[[Cell; 10]; 10]>(), 100);
+    }
+
+    #[test]
+    fn test_from_raw() {
+        for byte in [
+            0b0000_0000,
+            0b0000_0001,
+            0b0000_0110,
+            0b0001_0000,
+            0b1110_1011,
+        ] {
+            assert_eq!(Cell::from_raw(byte).raw(), byte);
+        }
+
+        let cell = Cell::from_raw(0b0001_0001);
+        assert!(cell.alive());
+        assert_eq!(cell.neighbours(), 8);
+
+        // 15 neighbours are not possible
+        let cell = Cell::from_raw(0b0001_1110);
+        assert!(!cell.alive());
+        assert_eq!(cell.raw(), 0b0001_0000);
+    }
+
+    #[test]
+    fn test_neighbours_full_range() {
+        let mut cell = Cell::new();
+        cell.spawn();
+        for _ in 0..Cell::MAX_NEIGHBOURS {
+            cell.add_neighbour();
+        }
+        assert_eq!(cell.neighbours(), 8);
+        assert_eq!(cell.to_string(), "00010001");
+
+        for _ in 0..Cell::MAX_NEIGHBOURS {
+            cell.remove_neighbour();
+        }
+        assert_eq!(cell.neighbours(), 0);
+        assert!(cell == 0b00000001);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "neighbour count overflow")]
+    fn test_neighbour_overflow() {
+        let mut cell = Cell::new();
+        cell.set_neighbours(Cell::MAX_NEIGHBOURS);
+        cell.add_neighbour();
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "neighbour count underflow")]
+    fn test_neighbour_underflow() {
+        Cell::new().remove_neighbour();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_too_many_neighbours() {
+        Cell::new().set_neighbours(Cell::MAX_NEIGHBOURS + 1);
+    }
+
+    #[test]
+    fn test_dying() {
+        let mut cell = Cell::new();
+        cell.set_neighbours(3);
+        cell.set_dying(2);
+        assert_eq!(cell.dying(), 2);
+        assert_eq!(cell.neighbours(), 3);
+        assert!(!cell.alive());
+        assert_eq!(cell.to_string(), "01000110");
+
+        cell.set_dying(7);
+        assert_eq!(cell.dying(), 7);
+        cell.set_dying(0);
+        assert!(cell == 0b00000110);
+    }
 }
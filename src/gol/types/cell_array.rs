@@ -1,28 +1,117 @@
-use std::fmt;
+use alloc::collections::VecDeque;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::ops::{Index, IndexMut};
+
+use crate::gol::types::{Cell, CellStorage, Grid, Neighbourhood, Rule, Topology};
+
+// Stack allocated 2D array of cells, stored as `Cell` unless another
+// CellStorage is given. Keeps a running count of the alive cells so the population
+// does not need to be recomputed by scanning the whole grid,
+// and of the alive cells per row so empty regions can be skipped.
+// Coordinates outside the board are mapped according to the Topology.
+#[derive(Debug, Copy, Clone)]
+pub struct CellArray<const H: usize, const W: usize, C = Cell> {
+    cells: [[C; W]; H],
+    alive_count: usize,
+    row_alive: [usize; H],
+    // Set once mut_cell or IndexMut handed out a cell, which may have been
+    // spawned or killed behind the running counts. They are rebuilt on the next
+    // spawn or kill, until then the counts are taken from the cells.
+    counts_stale: bool,
+    topology: Topology,
+    neighbourhood: Neighbourhood,
+    // Returned for reads outside a Fixed board, always empty
+    outside: C,
+}
 
-use crate::gol::types::Cell;
+impl<const H: usize, const W: usize, C: CellStorage> CellArray<H, W, C> {
+    pub fn new() -> CellArray<H, W, C> {
+        Self::with_topology(Topology::Toroidal)
+    }
 
-// Stack allocated 2D array of Cells
-#[derive(Debug, Copy, Clone)]
-pub struct CellArray<const H: usize, const W: usize>([[Cell; W]; H]);
+    // Panics for a Twisted topology with both shifts set
+    pub fn with_topology(topology: Topology) -> CellArray<H, W, C> {
+        if let Topology::Twisted { shift_x, shift_y } = topology {
+            assert!(
+                shift_x == 0 || shift_y == 0,
+                "a twisted board can only be shifted along one axis"
+            );
+        }
+        CellArray {
+            cells: [[C::empty(); W]; H],
+            alive_count: 0,
+            row_alive: [0; H],
+            counts_stale: false,
+            topology,
+            neighbourhood: Neighbourhood::Moore,
+            outside: C::empty(),
+        }
+    }
 
-impl<const H: usize, const W: usize> CellArray<H, W> {
-    pub fn new() -> CellArray<H, W> {
-        CellArray([[Cell::new(); W]; H])
+    pub fn topology(&self) -> Topology {
+        self.topology
     }
 
-    // Return a reference to the cell at (x, y)
-    pub fn cell(&self, x: isize, y: isize) -> &Cell {
-        let wrapped_x = ((x % W as isize + W as isize) % W as isize) as usize;
-        let wrapped_y = ((y % H as isize + H as isize) % H as isize) as usize;
-        &self.0[wrapped_y][wrapped_x]
+    pub fn neighbourhood(&self) -> Neighbourhood {
+        self.neighbourhood
     }
 
-    // Return a mutable reference to the cell at (x, y)
-    pub fn mut_cell(&mut self, x: isize, y: isize) -> &mut Cell {
-        let wrapped_x = ((x % W as isize + W as isize) % W as isize) as usize;
-        let wrapped_y = ((y % H as isize + H as isize) % H as isize) as usize;
-        &mut self.0[wrapped_y][wrapped_x]
+    // Switch the neighbourhood and recount the neighbours of every cell
+    pub fn set_neighbourhood(&mut self, neighbourhood: Neighbourhood) {
+        let alive: Vec<_> = self.live_coords().collect();
+        self.neighbourhood = neighbourhood;
+        self.reset();
+        for (x, y) in alive {
+            self.spawn(x, y);
+        }
+    }
+
+    // Position of (x, y) on the board, None if it lies outside a Fixed board
+    pub fn resolve(&self, x: isize, y: isize) -> Option<(usize, usize)> {
+        self.topology.resolve_point(x, y, W, H)
+    }
+
+    // Return a reference to the cell at (x, y), a dead cell outside a Fixed board
+    pub fn cell(&self, x: isize, y: isize) -> &C {
+        match self.resolve(x, y) {
+            Some((x, y)) => &self.cells[y][x],
+            None => &self.outside,
+        }
+    }
+
+    // Return a mutable reference to the cell at (x, y).
+    // The population stays right if the cell is spawned or killed through it.
+    // Panics outside a Fixed board.
+    pub fn mut_cell(&mut self, x: isize, y: isize) -> &mut C {
+        self.counts_stale = true;
+        self.cell_mut(x, y)
+    }
+
+    // mut_cell for the spawn and kill bookkeeping, which keeps the counts itself
+    fn cell_mut(&mut self, x: isize, y: isize) -> &mut C {
+        let Some((x, y)) = self.resolve(x, y) else {
+            panic!("({}, {}) is outside the board", x, y);
+        };
+        &mut self.cells[y][x]
+    }
+
+    // Alive cells in row y, counted from the cells if the running count is stale
+    fn row_count(&self, y: usize) -> usize {
+        if self.counts_stale {
+            self.cells[y].iter().filter(|cell| cell.is_alive()).count()
+        } else {
+            self.row_alive[y]
+        }
+    }
+
+    // Rebuild the running counts from the cells after mut_cell or IndexMut
+    fn sync_counts(&mut self) {
+        self.row_alive = core::array::from_fn(|y| self.row_count(y));
+        self.alive_count = self.row_alive.iter().sum();
+        self.counts_stale = false;
     }
 
     pub fn rows(&self) -> usize {
@@ -33,44 +122,455 @@ impl<const H: usize, const W: usize> CellArray<H, W> {
         W
     }
 
-    pub fn spawn(&mut self, x: isize, y: isize) {
-        let neighbour_coordinates = self.neighbour_coordinates(x, y);
+    // Kill every cell and clear all neighbour counts
+    pub fn reset(&mut self) {
+        for row in self.cells.iter_mut() {
+            row.fill(C::empty());
+        }
+        self.alive_count = 0;
+        self.row_alive = [0; H];
+        self.counts_stale = false;
+    }
 
-        let cell = self.mut_cell(x, y);
-        cell.spawn();
+    // Number of alive cells on the board
+    pub fn population(&self) -> usize {
+        if self.counts_stale {
+            (0..H).map(|y| self.row_count(y)).sum()
+        } else {
+            self.alive_count
+        }
+    }
 
-        for (nx, ny) in neighbour_coordinates.iter() {
-            let neighbour_cell = self.mut_cell(*nx, *ny);
-            neighbour_cell.add_neighbour();
+    // Number of alive cells in row y
+    pub fn row_population(&self, y: usize) -> usize {
+        self.row_count(y)
+    }
+
+    // Whether row y or one of the rows next to it has an alive cell.
+    // Only such rows can change in the next generation. Crossing the left or right
+    // edge of a board twisted by shift_y lands in another row, so there every row
+    // counts as active.
+    pub fn row_is_active(&self, y: usize) -> bool {
+        if let Topology::Twisted { shift_y, .. } = self.topology {
+            if shift_y != 0 {
+                return true;
+            }
         }
+        let y = y as isize;
+        (y - 1..=y + 1).any(|ny| {
+            self.topology
+                .resolve(ny, H)
+                .is_some_and(|ny| self.row_count(ny) > 0)
+        })
+    }
+
+    // The rows of the board from top to bottom, for scanline processing
+    pub fn rows_iter(&self) -> impl Iterator<Item = &[C; W]> + '_ {
+        self.cells.iter()
+    }
+
+    // Iterate over the (x, y) coordinates of all alive cells, row by row
+    pub fn live_coords(&self) -> impl Iterator<Item = (isize, isize)> + '_ {
+        self.cells.iter().enumerate().flat_map(|(y, row)| {
+            row.iter()
+                .enumerate()
+                .filter(|(_, cell)| cell.is_alive())
+                .map(move |(x, _)| (x as isize, y as isize))
+        })
+    }
+
+    // (min_x, min_y, max_x, max_y) of the alive cells, None on an empty board.
+    // Board coordinates are used as they are, a pattern that wraps around
+    // an edge spans the whole width or height.
+    pub fn bounding_box(&self) -> Option<(usize, usize, usize, usize)> {
+        self.live_coords().fold(None, |bounds, (x, y)| {
+            let (x, y) = (x as usize, y as usize);
+            Some(match bounds {
+                None => (x, y, x, y),
+                Some((min_x, min_y, max_x, max_y)) => {
+                    (min_x.min(x), min_y.min(y), max_x.max(x), max_y.max(y))
+                }
+            })
+        })
+    }
+
+    // Alive states as nested rows of columns, for interop with 2D grid APIs
+    pub fn to_bool_grid(&self) -> Vec<Vec<bool>> {
+        self.rows_iter()
+            .map(|row| row.iter().map(|cell| cell.is_alive()).collect())
+            .collect()
+    }
+
+    // Runs of alive cells in row y as (start x, length), left to right.
+    // Spans do not wrap around the edge.
+    pub fn row_spans(&self, y: usize) -> Vec<(usize, usize)> {
+        let mut spans = Vec::new();
+        let mut start = None;
+
+        for (x, cell) in self.cells[y].iter().enumerate() {
+            match (cell.is_alive(), start) {
+                (true, None) => start = Some(x),
+                (false, Some(s)) => {
+                    spans.push((s, x - s));
+                    start = None;
+                }
+                _ => {}
+            }
+        }
+        if let Some(s) = start {
+            spans.push((s, W - s));
+        }
+
+        spans
+    }
+
+    // row_spans of every row, top to bottom
+    pub fn all_spans(&self) -> Vec<Vec<(usize, usize)>> {
+        (0..H).map(|y| self.row_spans(y)).collect()
+    }
+
+    // Find every origin (x, y) at which the pattern occurs on the board.
+    // A match requires all pattern cells to be alive and every other cell
+    // inside the pattern's bounding box to be dead. Wraps around the edges.
+    pub fn find_all(&self, pattern: &[(isize, isize)]) -> Vec<(isize, isize)> {
+        if pattern.is_empty() {
+            return Vec::new();
+        }
+
+        let min_x = pattern.iter().map(|&(px, _)| px).min().unwrap();
+        let max_x = pattern.iter().map(|&(px, _)| px).max().unwrap();
+        let min_y = pattern.iter().map(|&(_, py)| py).min().unwrap();
+        let max_y = pattern.iter().map(|&(_, py)| py).max().unwrap();
+
+        let mut matches = Vec::new();
+        for y in 0..H as isize {
+            for x in 0..W as isize {
+                let is_match = (min_y..=max_y).all(|py| {
+                    (min_x..=max_x).all(|px| {
+                        let expected = pattern.contains(&(px, py));
+                        self.cell(x + px, y + py).is_alive() == expected
+                    })
+                });
+
+                if is_match {
+                    matches.push((x, y));
+                }
+            }
+        }
+        matches
+    }
+
+    // Area enclosed by the convex hull of the alive cells (without wrapping).
+    // Built with Andrew's monotone chain and measured with the shoelace formula,
+    // so fewer than three non-collinear cells have an area of 0.
+    pub fn hull_area(&self) -> f64 {
+        // live_coords yields the points sorted by (y, x)
+        let points: Vec<(isize, isize)> = self.live_coords().map(|(x, y)| (y, x)).collect();
+        if points.len() < 3 {
+            return 0.0;
+        }
+
+        let cross = |o: (isize, isize), a: (isize, isize), b: (isize, isize)| {
+            (a.0 - o.0) * (b.1 - o.1) - (a.1 - o.1) * (b.0 - o.0)
+        };
+
+        let mut hull: Vec<(isize, isize)> = Vec::with_capacity(points.len() * 2);
+        for pass in [points.clone(), points.into_iter().rev().collect()] {
+            let start = hull.len();
+            for point in pass {
+                while hull.len() >= start + 2
+                    && cross(hull[hull.len() - 2], hull[hull.len() - 1], point) <= 0
+                {
+                    hull.pop();
+                }
+                hull.push(point);
+            }
+            // The last point of each pass is the first of the next
+            hull.pop();
+        }
+
+        let twice_area: isize = (0..hull.len())
+            .map(|i| {
+                let (a, b) = (hull[i], hull[(i + 1) % hull.len()]);
+                a.0 * b.1 - b.0 * a.1
+            })
+            .sum();
+        twice_area.abs() as f64 / 2.0
+    }
+
+    // Group the alive cells into 8-connected clusters. Only Toroidal and Twisted
+    // boards connect across the edges, their coordinates are unwrapped relative to
+    // the first cell found in each cluster, so a cluster straddling an edge keeps
+    // its shape (and may leave the board range). On other boards every coordinate
+    // lies on the board.
+    pub fn clusters(&self) -> Vec<Vec<(isize, isize)>> {
+        let wraps = matches!(self.topology, Topology::Toroidal | Topology::Twisted { .. });
+        let mut visited = vec![false; H * W];
+        let mut clusters = Vec::new();
+
+        for (x, y) in self.live_coords() {
+            if visited[y as usize * W + x as usize] {
+                continue;
+            }
+            visited[y as usize * W + x as usize] = true;
+
+            let mut cluster = Vec::new();
+            let mut queue = VecDeque::from([(x, y)]);
+            while let Some((cx, cy)) = queue.pop_front() {
+                cluster.push((cx, cy));
+
+                for (nx, ny) in self.neighbour_coordinates(cx, cy) {
+                    let Some((rx, ry)) = self.resolve(nx, ny) else {
+                        continue;
+                    };
+                    // A Reflective edge only leads back to the cell or its neighbours
+                    if !wraps && (rx as isize, ry as isize) != (nx, ny) {
+                        continue;
+                    }
+                    let index = ry * W + rx;
+                    if !visited[index] && self.cells[ry][rx].is_alive() {
+                        visited[index] = true;
+                        queue.push_back((nx, ny));
+                    }
+                }
+            }
+            clusters.push(cluster);
+        }
+
+        clusters
+    }
+
+    // The alive cells in a form shared by every rotation and reflection of the board.
+    // See canonical_form_of.
+    pub fn canonical_form(&self) -> Vec<(isize, isize)> {
+        let coords: Vec<(isize, isize)> = self.live_coords().collect();
+        canonical_form_of(&coords)
+    }
+
+    // 64-bit FNV-1a hash over the cell states, one byte per cell
+    // laid out like `Cell`. Identical boards always produce the same fingerprint.
+    pub fn fingerprint(&self) -> u64 {
+        const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+        const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+        let mut hash = FNV_OFFSET;
+        for row in self.cells.iter() {
+            for cell in row.iter() {
+                let byte = (cell.dying() << 5) | (cell.neighbours() << 1) | cell.is_alive() as u8;
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+        }
+        hash
+    }
+
+    pub fn spawn(&mut self, x: isize, y: isize) {
+        Grid::spawn(self, x, y);
     }
 
     pub fn kill_cell(&mut self, x: isize, y: isize) {
-        let neighbour_coordinates = self.neighbour_coordinates(x, y);
+        Grid::kill_cell(self, x, y);
+    }
+
+    // Spawn a dead cell or kill an alive one, keeping the neighbour counts right
+    pub fn toggle_cell(&mut self, x: isize, y: isize) {
+        if self.cell(x, y).is_alive() {
+            self.kill_cell(x, y);
+        } else {
+            self.spawn(x, y);
+        }
+    }
+
+    // Spawn the pattern at every (i * step_x, j * step_y) offset on the board.
+    // Copies that reach past the edge are handled like spawn does.
+    pub fn tile(&mut self, pattern: &[(isize, isize)], step_x: usize, step_y: usize) {
+        assert!(step_x > 0 && step_y > 0, "tile steps must be positive");
+
+        for oy in (0..H).step_by(step_y) {
+            for ox in (0..W).step_by(step_x) {
+                for &(x, y) in pattern {
+                    self.spawn(ox as isize + x, oy as isize + y);
+                }
+            }
+        }
+    }
 
-        let cell = self.mut_cell(x, y);
-        cell.kill();
+    // Spawn every cell from (x0, y0) to (x1, y1) inclusive, nothing if x1 < x0 or y1 < y0.
+    // Cells past the edge are handled like spawn does.
+    pub fn fill_rect(&mut self, x0: isize, y0: isize, x1: isize, y1: isize) {
+        for y in y0..=y1 {
+            for x in x0..=x1 {
+                self.spawn(x, y);
+            }
+        }
+    }
 
-        for (nx, ny) in neighbour_coordinates.iter() {
-            let neighbour_cell = self.mut_cell(*nx, *ny);
-            neighbour_cell.remove_neighbour();
+    // Spawn the outermost rows and columns, e.g. as a wall
+    pub fn fill_border(&mut self) {
+        let (right, bottom) = (W as isize - 1, H as isize - 1);
+        self.fill_rect(0, 0, right, 0);
+        self.fill_rect(0, bottom, right, bottom);
+        self.fill_rect(0, 0, 0, bottom);
+        self.fill_rect(right, 0, right, bottom);
+    }
+
+    // Spawn every true entry of the shape at (x + col, y + row).
+    // Rows may have different lengths, missing entries are dead.
+    pub fn stamp(&mut self, shape: &[&[bool]], x: isize, y: isize) {
+        for (row, entries) in shape.iter().enumerate() {
+            for (col, &alive) in entries.iter().enumerate() {
+                if alive {
+                    self.spawn(x + col as isize, y + row as isize);
+                }
+            }
+        }
+    }
+
+    // Write the next generation under `rule` into `dst` without reading the stored
+    // neighbour counts. The alive cells of the rows above, at and below each row are
+    // summed per column once, then a 3 wide window slides along those sums,
+    // so every cell costs a constant number of additions instead of 8 lookups.
+    // Same result as Grid::step_into for the Moore neighbourhood on untwisted boards.
+    pub fn next_generation_into(&self, dst: &mut CellArray<H, W, C>, rule: &Rule) {
+        assert!(
+            self.neighbourhood == Neighbourhood::Moore,
+            "the sliding window only counts Moore neighbours"
+        );
+        assert!(
+            !matches!(self.topology, Topology::Twisted { .. }),
+            "the sliding window does not follow twisted edges"
+        );
+        dst.reset();
+
+        let alive = |x: usize, y: usize| self.cells[y][x].is_alive() as u8;
+        // Column sums with the columns left of 0 and right of W - 1 at both ends
+        let mut sums = vec![0u8; W + 2];
+        // Rows above, at and below y that lie on the board
+        let mut rows: Vec<usize> = Vec::with_capacity(3);
+
+        for y in 0..H {
+            rows.clear();
+            rows.extend(
+                [y as isize - 1, y as isize, y as isize + 1]
+                    .iter()
+                    .filter_map(|&ny| self.topology.resolve(ny, H)),
+            );
+            for x in 0..W {
+                sums[x + 1] = rows.iter().map(|&ny| alive(x, ny)).sum();
+            }
+            sums[0] = self.topology.resolve(-1, W).map_or(0, |x| sums[x + 1]);
+            sums[W + 1] = self
+                .topology
+                .resolve(W as isize, W)
+                .map_or(0, |x| sums[x + 1]);
+
+            let mut window = sums[0] + sums[1];
+            for x in 0..W {
+                window += sums[x + 2];
+                let cell = &self.cells[y][x];
+                let neighbours = window - alive(x, y);
+                window -= sums[x];
+
+                let (cx, cy) = (x as isize, y as isize);
+                let dying = cell.dying();
+                // dst was reset, so setting a countdown never changes the counts
+                if dying > 0 {
+                    dst.cell_mut(cx, cy).set_dying(dying - 1);
+                } else if cell.is_alive() {
+                    if rule.survives(neighbours) {
+                        dst.spawn(cx, cy);
+                    } else {
+                        dst.cell_mut(cx, cy).set_dying(rule.dying_states());
+                    }
+                } else if rule.born(neighbours) {
+                    dst.spawn(cx, cy);
+                }
+            }
+        }
+    }
+
+    // Recount the neighbours of every cell from scratch and compare them
+    // with the counts kept up to date by spawn and kill_cell
+    pub fn verify_neighbour_counts(&self) -> bool {
+        (0..H).all(|y| {
+            (0..W).all(|x| {
+                let (x, y) = (x as isize, y as isize);
+                let count = self
+                    .neighbourhood
+                    .offsets()
+                    .iter()
+                    .filter_map(|&(dx, dy)| self.resolve(x + dx, y + dy))
+                    .filter(|&(nx, ny)| self.cells[ny][nx].is_alive())
+                    .count();
+                self.cell(x, y).neighbours() as usize == count
+            })
+        })
+    }
+
+    // Panics in debug builds if verify_neighbour_counts fails
+    pub fn debug_assert_neighbour_counts(&self) {
+        debug_assert!(
+            self.verify_neighbour_counts(),
+            "stored neighbour counts are out of sync"
+        );
+    }
+
+    // Whether every cell of the pattern lies on the board when its offsets are
+    // added to (x, y), without wrapping around an edge
+    pub fn fits(&self, pattern: &[(isize, isize)], x: isize, y: isize) -> bool {
+        pattern.iter().all(|&(dx, dy)| {
+            (0..W as isize).contains(&(x + dx)) && (0..H as isize).contains(&(y + dy))
+        })
+    }
+
+    // Whether the pattern fits at (x, y) and none of its cells is alive already,
+    // so stamping it there keeps its shape
+    pub fn can_place(&self, pattern: &[(isize, isize)], x: isize, y: isize) -> bool {
+        self.fits(pattern, x, y)
+            && pattern
+                .iter()
+                .all(|&(dx, dy)| !self.cell(x + dx, y + dy).is_alive())
+    }
+
+    // Middle of the board, rounded down on even sizes
+    pub fn center(&self) -> (isize, isize) {
+        (W as isize / 2, H as isize / 2)
+    }
+
+    // Spawn the pattern shifted so the middle of its bounding box is on center()
+    pub fn stamp_centered(&mut self, pattern: &[(isize, isize)]) {
+        let Some(&(first_x, first_y)) = pattern.first() else {
+            return;
+        };
+        let (mut min_x, mut min_y, mut max_x, mut max_y) = (first_x, first_y, first_x, first_y);
+        for &(x, y) in pattern {
+            min_x = min_x.min(x);
+            min_y = min_y.min(y);
+            max_x = max_x.max(x);
+            max_y = max_y.max(y);
+        }
+
+        let (cx, cy) = self.center();
+        let (dx, dy) = (cx - (min_x + max_x) / 2, cy - (min_y + max_y) / 2);
+        for &(x, y) in pattern {
+            self.spawn(x + dx, y + dy);
         }
     }
 
     pub fn neighbour_coordinates(&self, x: isize, y: isize) -> [(isize, isize); 8] {
-        [
-            (x.wrapping_sub(1), y.wrapping_sub(1)), // top_left
-            (x, y.wrapping_sub(1)),                 // top
-            (x.wrapping_add(1), y.wrapping_sub(1)), // top_right
-            (x.wrapping_sub(1), y),                 // left
-            (x.wrapping_add(1), y),                 // right
-            (x.wrapping_sub(1), y.wrapping_add(1)), // bottom_left
-            (x, y.wrapping_add(1)),                 // bottom
-            (x.wrapping_add(1), y.wrapping_add(1)), // bottom_right
-        ]
+        Grid::neighbour_coordinates(self, x, y)
+    }
+
+    // The Moore neighbours of (x, y) mapped onto the board as (column, row),
+    // in the same order as neighbour_coordinates.
+    // None for neighbours outside a Fixed board.
+    pub fn neighbour_indices(&self, x: isize, y: isize) -> [Option<(usize, usize)>; 8] {
+        self.neighbour_coordinates(x, y)
+            .map(|(nx, ny)| self.resolve(nx, ny))
     }
 
     #[allow(dead_code)]
+    #[cfg(feature = "std")]
     pub fn print(&self) {
         // Print the top border with column indices
         print!("   "); // Space for row indices
@@ -88,7 +588,7 @@ impl<const H: usize, const W: usize> CellArray<H, W> {
             print!("{:2}|", y); // Row index
             for x in 0..W {
                 let cell = self.cell(x as isize, y as isize);
-                let symbol = if cell.alive() { '*' } else { ' ' };
+                let symbol = if cell.is_alive() { '*' } else { ' ' };
                 print!(" {} |", symbol);
             }
             println!(); // End of the row with a side border
@@ -105,11 +605,146 @@ impl<const H: usize, const W: usize> CellArray<H, W> {
     }
 }
 
-impl<const H: usize, const W: usize> fmt::Display for CellArray<H, W> {
+impl<const H: usize, const W: usize, C: CellStorage> Default for CellArray<H, W, C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const H: usize, const W: usize, C: CellStorage> Grid for CellArray<H, W, C> {
+    type Cell = C;
+
+    fn rows(&self) -> usize {
+        H
+    }
+
+    fn cols(&self) -> usize {
+        W
+    }
+
+    fn cell(&self, x: isize, y: isize) -> &C {
+        CellArray::cell(self, x, y)
+    }
+
+    fn mut_cell(&mut self, x: isize, y: isize) -> &mut C {
+        self.cell_mut(x, y)
+    }
+
+    fn contains(&self, x: isize, y: isize) -> bool {
+        self.resolve(x, y).is_some()
+    }
+
+    fn neighbourhood(&self) -> Neighbourhood {
+        self.neighbourhood
+    }
+
+    fn population(&self) -> usize {
+        CellArray::population(self)
+    }
+
+    fn record_alive(&mut self, x: isize, y: isize, alive: bool) {
+        // The cell already has its new state, so a rebuild counts it
+        if self.counts_stale {
+            self.sync_counts();
+            return;
+        }
+        let (_, y) = self.resolve(x, y).unwrap();
+        if alive {
+            self.alive_count += 1;
+            self.row_alive[y] += 1;
+        } else {
+            self.alive_count -= 1;
+            self.row_alive[y] -= 1;
+        }
+    }
+}
+
+// Boards are equal when their cells, topology and neighbourhood are,
+// the bookkeeping follows from the cells
+impl<const H: usize, const W: usize, C: CellStorage + PartialEq> PartialEq for CellArray<H, W, C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cells == other.cells
+            && self.topology == other.topology
+            && self.neighbourhood == other.neighbourhood
+    }
+}
+
+impl<const H: usize, const W: usize, C: CellStorage + Eq> Eq for CellArray<H, W, C> {}
+
+impl<const H: usize, const W: usize, C: CellStorage + Hash> Hash for CellArray<H, W, C> {
+    fn hash<S: Hasher>(&self, state: &mut S) {
+        self.cells.hash(state);
+        self.topology.hash(state);
+        self.neighbourhood.hash(state);
+    }
+}
+
+// array[(x, y)] is the same as array.cell(x, y), wrapping included
+impl<const H: usize, const W: usize, C: CellStorage> Index<(isize, isize)> for CellArray<H, W, C> {
+    type Output = C;
+
+    fn index(&self, (x, y): (isize, isize)) -> &C {
+        self.cell(x, y)
+    }
+}
+
+// array[(x, y)] = ... is the same as array.mut_cell(x, y).
+// Changing liveness this way bypasses the neighbour bookkeeping,
+// use spawn/kill_cell to bring cells to life or kill them.
+// The population is still counted right, see mut_cell.
+impl<const H: usize, const W: usize, C: CellStorage> IndexMut<(isize, isize)>
+    for CellArray<H, W, C>
+{
+    fn index_mut(&mut self, (x, y): (isize, isize)) -> &mut C {
+        self.mut_cell(x, y)
+    }
+}
+
+// Apply all 8 symmetries of the square (rotations and reflections) to the
+// coordinates, move each result to the origin and return the lexicographically
+// smallest sorted set. Shapes that are equal up to symmetry share a canonical form.
+pub fn canonical_form_of(coords: &[(isize, isize)]) -> Vec<(isize, isize)> {
+    // (x sign, y sign, swap axes)
+    let symmetries = [
+        (1, 1, false),
+        (-1, 1, false),
+        (1, -1, false),
+        (-1, -1, false),
+        (1, 1, true),
+        (-1, 1, true),
+        (1, -1, true),
+        (-1, -1, true),
+    ];
+
+    symmetries
+        .iter()
+        .map(|&(sign_x, sign_y, swap)| {
+            let transformed: Vec<(isize, isize)> = coords
+                .iter()
+                .map(|&(x, y)| if swap { (y, x) } else { (x, y) })
+                .map(|(x, y)| (sign_x * x, sign_y * y))
+                .collect();
+            let min_x = transformed.iter().map(|&(x, _)| x).min().unwrap_or(0);
+            let min_y = transformed.iter().map(|&(_, y)| y).min().unwrap_or(0);
+
+            let mut normalized: Vec<(isize, isize)> = transformed
+                .into_iter()
+                .map(|(x, y)| (x - min_x, y - min_y))
+                .collect();
+            normalized.sort();
+            normalized
+        })
+        .min()
+        .unwrap()
+}
+
+impl<const H: usize, const W: usize, C: CellStorage + fmt::Display> fmt::Display
+    for CellArray<H, W, C>
+{
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         for i in 0..H {
             for j in 0..W {
-                write!(f, "{} ", self.0[i][j])?;
+                write!(f, "{} ", self.cells[i][j])?;
             }
             writeln!(f)?;
         }
@@ -119,7 +754,10 @@ impl<const H: usize, const W: usize> fmt::Display for CellArray<H, W> {
 
 #[cfg(test)]
 mod test_cell_array {
-    use super::CellArray;
+    use super::{CellArray, Neighbourhood, Rule, Topology};
+    use crate::gol::types::Grid;
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
 
     const ARRAY_H: usize = 5;
     const ARRAY_W: usize = 5;
@@ -258,6 +896,536 @@ mod test_cell_array {
         assert_eq!(destination.alive(), true);
     }
 
+    #[test]
+    fn test_rows_and_cols() {
+        // H is the number of rows, W the number of columns
+        let cell_array = CellArray::<3, 7>::new();
+        assert_eq!(cell_array.rows(), 3);
+        assert_eq!(cell_array.cols(), 7);
+        assert_eq!(cell_array.to_bool_grid().len(), 3);
+        assert_eq!(cell_array.to_bool_grid()[0].len(), 7);
+    }
+
+    #[test]
+    fn test_population() {
+        let mut cell_array = setup();
+        assert_eq!(cell_array.population(), 0);
+
+        cell_array.spawn(1, 1);
+        cell_array.spawn(2, 1);
+        assert_eq!(cell_array.population(), 2);
+
+        // Spawning twice must not count the cell or its neighbours twice
+        cell_array.spawn(1, 1);
+        assert_eq!(cell_array.population(), 2);
+        assert_eq!(cell_array.cell(2, 1).neighbours(), 1);
+
+        cell_array.kill_cell(1, 1);
+        cell_array.kill_cell(1, 1);
+        assert_eq!(cell_array.population(), 1);
+        assert_eq!(cell_array.cell(2, 1).neighbours(), 0);
+    }
+
+    #[test]
+    fn test_row_population() {
+        let mut cell_array = CellArray::<6, 4>::new();
+        cell_array.spawn(0, 1);
+        cell_array.spawn(3, 1);
+        cell_array.spawn(2, -1);
+        assert_eq!(cell_array.row_population(1), 2);
+        assert_eq!(cell_array.row_population(5), 1);
+
+        let active: Vec<usize> = (0..6).filter(|&y| cell_array.row_is_active(y)).collect();
+        assert_eq!(active, vec![0, 1, 2, 4, 5]);
+
+        cell_array.kill_cell(0, 1);
+        cell_array.kill_cell(3, 1);
+        assert_eq!(cell_array.row_population(1), 0);
+        assert!(!cell_array.row_is_active(2));
+    }
+
+    #[test]
+    fn test_reset() {
+        let mut cell_array = setup();
+        cell_array.spawn(1, 1);
+        cell_array.spawn(2, 2);
+        cell_array.reset();
+
+        assert_eq!(cell_array.population(), 0);
+        assert_eq!(cell_array.to_string(), setup().to_string());
+    }
+
+    #[test]
+    fn test_live_coords() {
+        let mut cell_array = setup();
+        cell_array.spawn(3, 0);
+        cell_array.spawn(1, 2);
+        cell_array.spawn(-1, 4);
+
+        let coords: Vec<(isize, isize)> = cell_array.live_coords().collect();
+        assert_eq!(coords, vec![(3, 0), (1, 2), (4, 4)]);
+    }
+
+    #[test]
+    fn test_bounding_box() {
+        let mut cell_array = CellArray::<10, 10>::new();
+        assert_eq!(cell_array.bounding_box(), None);
+
+        cell_array.spawn(3, 4);
+        assert_eq!(cell_array.bounding_box(), Some((3, 4, 3, 4)));
+
+        cell_array.kill_cell(3, 4);
+        for (x, y) in [(2, 0), (2, 1), (2, 2), (1, 2), (0, 1)] {
+            cell_array.spawn(x + 5, y + 6);
+        }
+        assert_eq!(cell_array.bounding_box(), Some((5, 6, 7, 8)));
+    }
+
+    #[test]
+    fn test_to_bool_grid() {
+        let mut cell_array = CellArray::<3, 4>::new();
+        cell_array.spawn(3, 1);
+
+        let grid = cell_array.to_bool_grid();
+        assert_eq!(grid.len(), 3);
+        assert!(grid.iter().all(|row| row.len() == 4));
+        assert!(grid[1][3]);
+        assert_eq!(grid.iter().flatten().filter(|&&alive| alive).count(), 1);
+    }
+
+    #[test]
+    fn test_find_all_blocks() {
+        let block = [(0, 0), (1, 0), (0, 1), (1, 1)];
+        let mut cell_array = CellArray::<10, 10>::new();
+        for &(ox, oy) in &[(1, 1), (5, 1), (9, 6)] {
+            for &(px, py) in &block {
+                cell_array.spawn(ox + px, oy + py);
+            }
+        }
+
+        // The third block wraps around the right edge
+        assert_eq!(cell_array.find_all(&block), vec![(1, 1), (5, 1), (9, 6)]);
+
+        // Part of a block is not an exact match
+        assert!(cell_array.find_all(&[(0, 0), (1, 0), (0, 1)]).is_empty());
+    }
+
+    #[test]
+    fn test_hull_area() {
+        let mut cell_array = CellArray::<10, 10>::new();
+        assert_eq!(cell_array.hull_area(), 0.0);
+
+        // Collinear cells enclose nothing
+        for x in 0..5 {
+            cell_array.spawn(x, 0);
+        }
+        assert_eq!(cell_array.hull_area(), 0.0);
+
+        // Right triangle with legs of 4, interior cells do not matter
+        cell_array.spawn(0, 4);
+        cell_array.spawn(1, 1);
+        assert_eq!(cell_array.hull_area(), 8.0);
+
+        // Completing the square doubles it
+        cell_array.spawn(4, 4);
+        assert_eq!(cell_array.hull_area(), 16.0);
+    }
+
+    #[test]
+    fn test_clusters() {
+        let mut cell_array = CellArray::<10, 10>::new();
+        // Block straddling the left/right edge
+        for &(x, y) in &[(9, 1), (0, 1), (9, 2), (0, 2)] {
+            cell_array.spawn(x, y);
+        }
+        // Diagonal pair, connected through the corner
+        cell_array.spawn(4, 5);
+        cell_array.spawn(5, 6);
+
+        let mut clusters = cell_array.clusters();
+        clusters.sort_by_key(|cluster| cluster.len());
+        assert_eq!(clusters.len(), 2);
+        assert_eq!(clusters[0].len(), 2);
+        assert_eq!(clusters[1].len(), 4);
+
+        // The wrapped block keeps its 2x2 shape
+        let xs: Vec<isize> = clusters[1].iter().map(|&(x, _)| x).collect();
+        assert_eq!(xs.iter().max().unwrap() - xs.iter().min().unwrap(), 1);
+
+        // Other edges do not connect, and no coordinate leaves the board
+        for topology in [Topology::Fixed, Topology::Reflective] {
+            let mut cell_array = CellArray::<10, 10>::with_topology(topology);
+            for &(x, y) in &[(9, 1), (0, 1), (9, 2), (0, 2), (0, 0)] {
+                cell_array.spawn(x, y);
+            }
+
+            let mut clusters = cell_array.clusters();
+            clusters.sort_by_key(|cluster| cluster.len());
+            assert_eq!(
+                clusters.iter().map(Vec::len).collect::<Vec<_>>(),
+                vec![2, 3],
+                "{:?}",
+                topology
+            );
+            assert!(clusters
+                .iter()
+                .flatten()
+                .all(|&(x, y)| (0..10).contains(&x) && (0..10).contains(&y)));
+        }
+    }
+
+    #[test]
+    fn test_canonical_form() {
+        let glider = [(2, 0), (2, 1), (2, 2), (1, 2), (0, 1)];
+
+        let mut original = CellArray::<10, 10>::new();
+        let mut mirrored = CellArray::<10, 10>::new();
+        for &(x, y) in &glider {
+            original.spawn(x + 1, y + 1);
+            mirrored.spawn(7 - x, y + 4);
+        }
+
+        assert_ne!(
+            original.live_coords().collect::<Vec<_>>(),
+            mirrored.live_coords().collect::<Vec<_>>()
+        );
+        assert_eq!(original.canonical_form(), mirrored.canonical_form());
+
+        // A different shape has a different canonical form
+        let mut block = CellArray::<10, 10>::new();
+        for &(x, y) in &[(0, 0), (1, 0), (0, 1), (1, 1), (2, 2)] {
+            block.spawn(x, y);
+        }
+        assert_ne!(original.canonical_form(), block.canonical_form());
+    }
+
+    #[test]
+    fn test_index() {
+        let mut cell_array = setup();
+        cell_array.spawn(4, 0);
+
+        assert!(cell_array[(-1, 0)].alive());
+        assert!(std::ptr::eq(&cell_array[(-1, 0)], cell_array.cell(-1, 0)));
+        assert!(std::ptr::eq(&cell_array[(-1, 0)], cell_array.cell(4, 0)));
+        assert_eq!(cell_array[(0, 0)].neighbours(), 1);
+
+        // Raw mutation does not touch the neighbours
+        cell_array[(2, 2)].spawn();
+        assert!(cell_array.cell(2, 2).alive());
+        assert_eq!(cell_array.cell(1, 1).neighbours(), 0);
+
+        // but the population follows it
+        assert_eq!(cell_array.population(), 2);
+        assert_eq!(cell_array.row_population(2), 1);
+        assert!(cell_array.row_is_active(3));
+        cell_array.spawn(0, 4);
+        assert_eq!(cell_array.population(), 3);
+        cell_array.mut_cell(4, 0).kill();
+        assert_eq!(cell_array.population(), 2);
+        assert_eq!(cell_array.row_population(0), 0);
+        cell_array.kill_cell(0, 4);
+        assert_eq!(cell_array.population(), 1);
+    }
+
+    #[test]
+    fn test_fingerprint() {
+        let mut a = setup();
+        let mut b = setup();
+        a.spawn(1, 1);
+        b.spawn(1, 1);
+        assert_eq!(a.fingerprint(), b.fingerprint());
+
+        b.spawn(3, 3);
+        assert_ne!(a.fingerprint(), b.fingerprint());
+
+        // A dying countdown is part of the state
+        let mut c = setup();
+        let d = setup();
+        c.mut_cell(2, 2).set_dying(1);
+        assert_ne!(c.fingerprint(), d.fingerprint());
+    }
+
+    #[test]
+    fn test_copy() {
+        let mut source = setup();
+        source.spawn(1, 1);
+        source.spawn(1, 2);
+        source.mut_cell(3, 3).set_neighbours(5);
+
+        // Plain assignment copies the whole array, no unsafe copy needed
+        let mut destination = setup();
+        destination.spawn(0, 0);
+        destination = source;
+
+        for y in 0..ARRAY_H as isize {
+            for x in 0..ARRAY_W as isize {
+                assert_eq!(destination.cell(x, y).raw(), source.cell(x, y).raw());
+            }
+        }
+        assert_eq!(destination.population(), source.population());
+        assert!(!destination.cell(0, 0).alive());
+        assert_eq!(destination.cell(3, 3).neighbours(), 5);
+    }
+
+    #[test]
+    fn test_neighbour_indices() {
+        let cell_array = CellArray::<4, 6>::new();
+        assert_eq!(
+            cell_array.neighbour_indices(0, 0),
+            [
+                Some((5, 3)),
+                Some((0, 3)),
+                Some((1, 3)),
+                Some((5, 0)),
+                Some((1, 0)),
+                Some((5, 1)),
+                Some((0, 1)),
+                Some((1, 1)),
+            ]
+        );
+
+        let fixed = CellArray::<4, 6>::with_topology(Topology::Fixed);
+        let inside: Vec<_> = fixed
+            .neighbour_indices(5, 3)
+            .into_iter()
+            .flatten()
+            .collect();
+        assert_eq!(inside, vec![(4, 2), (5, 2), (4, 3)]);
+    }
+
+    #[test]
+    fn test_toggle_cell() {
+        let mut cell_array = setup();
+        cell_array.spawn(1, 1);
+        cell_array.spawn(2, 1);
+        let original = cell_array;
+
+        cell_array.toggle_cell(2, 2);
+        assert!(cell_array.cell(2, 2).alive());
+        assert_eq!(cell_array.cell(1, 1).neighbours(), 2);
+        assert_eq!(cell_array.population(), 3);
+
+        cell_array.toggle_cell(2, 2);
+        assert_eq!(cell_array.fingerprint(), original.fingerprint());
+        assert_eq!(cell_array.population(), 2);
+    }
+
+    #[test]
+    fn test_spawn_surrounded_cell() {
+        let mut cell_array = setup();
+        for (x, y) in cell_array.neighbour_coordinates(2, 2) {
+            cell_array.spawn(x, y);
+        }
+        assert_eq!(cell_array.cell(2, 2).neighbours(), 8);
+
+        cell_array.spawn(2, 2);
+        assert!(cell_array.cell(2, 2).alive());
+        assert_eq!(cell_array.cell(2, 2).neighbours(), 8);
+        assert_eq!(cell_array.population(), 9);
+
+        // Ring corners see the centre and two ring cells, edge centres four
+        assert_eq!(cell_array.cell(1, 1).neighbours(), 3);
+        assert_eq!(cell_array.cell(2, 1).neighbours(), 5);
+    }
+
+    #[test]
+    fn test_edge_topologies() {
+        // A vertical pair on the left edge
+        let neighbours_of_edge_cell = |topology| {
+            let mut cell_array = CellArray::<5, 5>::with_topology(topology);
+            cell_array.spawn(0, 2);
+            cell_array.spawn(0, 3);
+            cell_array.cell(0, 2).neighbours()
+        };
+
+        assert_eq!(neighbours_of_edge_cell(Topology::Toroidal), 1);
+        assert_eq!(neighbours_of_edge_cell(Topology::Fixed), 1);
+        // The mirrored column adds the cell itself and its partner once more
+        assert_eq!(neighbours_of_edge_cell(Topology::Reflective), 3);
+
+        let mut toroidal = CellArray::<5, 5>::new();
+        let mut reflective = CellArray::<5, 5>::with_topology(Topology::Reflective);
+        toroidal.spawn(0, 2);
+        reflective.spawn(0, 2);
+        assert_eq!(toroidal.cell(4, 2).neighbours(), 1);
+        assert_eq!(reflective.cell(4, 2).neighbours(), 0);
+        assert!(reflective.cell(-1, 2).alive());
+    }
+
+    #[test]
+    fn test_can_place() {
+        let glider = [(2, 0), (2, 1), (2, 2), (1, 2), (0, 1)];
+        let mut cell_array = setup();
+        assert!(cell_array.can_place(&glider, 0, 0));
+        assert!(cell_array.can_place(&glider, 2, 2));
+        // Would wrap around the right and the top edge
+        assert!(!cell_array.can_place(&glider, 3, 0));
+        assert!(!cell_array.can_place(&glider, 0, -1));
+
+        cell_array.spawn(1, 3);
+        assert!(!cell_array.can_place(&glider, 0, 1));
+        assert!(cell_array.fits(&glider, 0, 1));
+        assert!(cell_array.can_place(&glider, 1, 1));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_twisted_both_axes() {
+        CellArray::<5, 5>::with_topology(Topology::Twisted {
+            shift_x: 1,
+            shift_y: 1,
+        });
+    }
+
+    #[test]
+    fn test_fill_rect() {
+        let mut cell_array = setup();
+        cell_array.fill_rect(1, 1, 3, 3);
+        assert_eq!(cell_array.population(), 9);
+        assert_eq!(cell_array.cell(2, 2).neighbours(), 8);
+        assert!(!cell_array.cell(0, 0).alive());
+        assert!(cell_array.verify_neighbour_counts());
+
+        // Wraps around the edges, overlapping cells are spawned once
+        let mut cell_array = setup();
+        cell_array.fill_rect(4, -1, 5, 0);
+        assert_eq!(
+            cell_array.live_coords().collect::<Vec<_>>(),
+            vec![(0, 0), (4, 0), (0, 4), (4, 4)]
+        );
+        cell_array.fill_rect(0, 0, 9, 0);
+        assert_eq!(cell_array.population(), 7);
+
+        let mut cell_array = setup();
+        cell_array.fill_rect(3, 3, 2, 2);
+        assert_eq!(cell_array.population(), 0);
+    }
+
+    #[test]
+    fn test_fill_border() {
+        let mut cell_array = CellArray::<4, 6>::with_topology(Topology::Fixed);
+        cell_array.fill_border();
+        assert_eq!(cell_array.population(), 16);
+        assert!(cell_array.cell(5, 3).alive());
+        assert!(!cell_array.cell(1, 1).alive() && !cell_array.cell(4, 2).alive());
+        assert_eq!(cell_array.cell(1, 1).neighbours(), 5);
+        assert!(cell_array.verify_neighbour_counts());
+    }
+
+    #[test]
+    fn test_twisted_edges() {
+        let topology = Topology::Twisted {
+            shift_x: 3,
+            shift_y: 0,
+        };
+        let mut array = CellArray::<6, 8>::with_topology(topology);
+        for x in 1..4 {
+            array.spawn(x, 0);
+        }
+
+        // The top of the blinker leaves the top edge
+        // and enters the bottom row 3 columns further left
+        let mut next = CellArray::with_topology(topology);
+        array.step_into(&mut next, &Rule::conway(), |_, _| {});
+        assert_eq!(
+            next.live_coords().collect::<Vec<_>>(),
+            vec![(2, 0), (2, 1), (7, 5)]
+        );
+        assert!(next.cell(2, -1).alive());
+        assert!(next.verify_neighbour_counts());
+
+        let mut back = CellArray::with_topology(topology);
+        next.step_into(&mut back, &Rule::conway(), |_, _| {});
+        assert_eq!(back, array);
+    }
+
+    #[test]
+    fn test_von_neumann_neighbourhood() {
+        let mut moore = setup();
+        moore.spawn(1, 1);
+        moore.spawn(2, 1);
+        assert_eq!(moore.cell(2, 2).neighbours(), 2);
+
+        let mut von_neumann = moore;
+        von_neumann.set_neighbourhood(Neighbourhood::VonNeumann);
+        // (1, 1) is diagonal to (2, 2) and no longer counts
+        assert_eq!(von_neumann.cell(2, 2).neighbours(), 1);
+        assert_eq!(von_neumann.cell(1, 2).neighbours(), 1);
+        assert_eq!(von_neumann.cell(1, 1).neighbours(), 1);
+
+        von_neumann.spawn(3, 3);
+        assert_eq!(von_neumann.cell(2, 2).neighbours(), 1);
+        assert_eq!(von_neumann.cell(3, 2).neighbours(), 1);
+        von_neumann.kill_cell(1, 1);
+        assert_eq!(von_neumann.cell(1, 0).neighbours(), 0);
+    }
+
+    #[test]
+    fn test_fixed_edges() {
+        let mut cell_array = CellArray::<5, 5>::with_topology(Topology::Fixed);
+        cell_array.spawn(0, 0);
+        cell_array.spawn(-1, 0);
+
+        assert_eq!(cell_array.population(), 1);
+        assert!(!cell_array.cell(5, 0).alive());
+        assert!(!cell_array.cell(-1, -1).alive());
+        assert_eq!(cell_array.cell(-1, -1).neighbours(), 0);
+        assert_eq!(cell_array.cell(4, 4).neighbours(), 0);
+        assert_eq!(cell_array.cell(1, 1).neighbours(), 1);
+
+        cell_array.kill_cell(0, 0);
+        assert_eq!(cell_array.cell(1, 1).neighbours(), 0);
+    }
+
+    #[test]
+    fn test_stamp() {
+        let mut cell_array = CellArray::<6, 6>::new();
+        let t_shape: [&[bool]; 3] = [&[true, true, true], &[false, true], &[false, true, false]];
+        cell_array.stamp(&t_shape, 1, 1);
+
+        let coords: Vec<_> = cell_array.live_coords().collect();
+        assert_eq!(coords, vec![(1, 1), (2, 1), (3, 1), (2, 2), (2, 3)]);
+
+        assert_eq!(cell_array.cell(2, 1).neighbours(), 3);
+        assert_eq!(cell_array.cell(2, 2).neighbours(), 4);
+        assert_eq!(cell_array.cell(2, 3).neighbours(), 1);
+        assert_eq!(cell_array.cell(1, 2).neighbours(), 4);
+        assert_eq!(cell_array.cell(2, 0).neighbours(), 3);
+    }
+
+    #[test]
+    fn test_tile() {
+        let mut cell_array = CellArray::<6, 6>::new();
+        cell_array.tile(&[(0, 0)], 2, 2);
+
+        assert_eq!(cell_array.population(), 9);
+        for y in 0..6 {
+            for x in 0..6 {
+                let cell = cell_array.cell(x, y);
+                match (x % 2 == 0, y % 2 == 0) {
+                    (true, true) => assert!(cell.alive() && cell.neighbours() == 0),
+                    (false, false) => assert_eq!(cell.neighbours(), 4),
+                    _ => assert_eq!(cell.neighbours(), 2),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_row_spans() {
+        let mut cell_array = CellArray::<3, 10>::new();
+        for x in [1, 2, 3, 6, 7, 9] {
+            cell_array.spawn(x, 1);
+        }
+
+        assert_eq!(cell_array.row_spans(1), vec![(1, 3), (6, 2), (9, 1)]);
+        assert!(cell_array.row_spans(0).is_empty());
+        assert_eq!(
+            cell_array.all_spans(),
+            vec![vec![], vec![(1, 3), (6, 2), (9, 1)], vec![]]
+        );
+    }
+
     #[test]
     fn test_glider() {
         let mut cell_array = setup();
@@ -413,4 +1581,158 @@ mod test_cell_array {
         assert_eq!(c25.alive(), false);
         assert_eq!(c25_neighbours, 0);
     }
+
+    fn hash_of(array: &CellArray<ARRAY_H, ARRAY_W>) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        array.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn test_eq_and_hash() {
+        let mut a = setup();
+        let mut b = setup();
+        // Same cells spawned in a different order
+        for (x, y) in [(1, 1), (2, 1), (3, 1)] {
+            a.spawn(x, y);
+        }
+        for (x, y) in [(3, 1), (1, 1), (2, 1)] {
+            b.spawn(x, y);
+        }
+        assert_eq!(a, b);
+        assert_eq!(hash_of(&a), hash_of(&b));
+
+        b.spawn(4, 4);
+        assert_ne!(a, b);
+        assert_ne!(hash_of(&a), hash_of(&b));
+
+        // The same cells on boards with different edges are different boards
+        let mut fixed = CellArray::with_topology(Topology::Fixed);
+        for (x, y) in [(1, 1), (2, 1), (3, 1)] {
+            fixed.spawn(x, y);
+        }
+        assert_ne!(a, fixed);
+        assert_ne!(hash_of(&a), hash_of(&fixed));
+        fixed = a;
+        fixed.set_neighbourhood(Neighbourhood::VonNeumann);
+        assert_ne!(a, fixed);
+    }
+
+    #[test]
+    fn test_stamp_centered() {
+        let mut array = CellArray::<7, 9>::new();
+        assert_eq!(array.center(), (4, 3));
+
+        array.stamp_centered(&[(10, -2)]);
+        assert_eq!(array.population(), 1);
+        assert!(array.cell(4, 3).alive());
+
+        // A 3x1 blinker has its middle cell on the center
+        let mut array = CellArray::<7, 9>::new();
+        array.stamp_centered(&[(0, 0), (1, 0), (2, 0)]);
+        assert_eq!(
+            array.live_coords().collect::<Vec<_>>(),
+            vec![(3, 3), (4, 3), (5, 3)]
+        );
+
+        array.stamp_centered(&[]);
+        assert_eq!(array.population(), 3);
+    }
+
+    #[test]
+    fn test_verify_neighbour_counts() {
+        let mut array = CellArray::<6, 8>::with_topology(Topology::Fixed);
+        array.tile(&[(0, 0), (1, 0), (1, 1)], 3, 3);
+        array.kill_cell(1, 1);
+        assert!(array.verify_neighbour_counts());
+        array.debug_assert_neighbour_counts();
+
+        array.set_neighbourhood(Neighbourhood::VonNeumann);
+        assert!(array.verify_neighbour_counts());
+
+        // Raw mutation skips the bookkeeping
+        array[(4, 4)].set_neighbours(3);
+        assert!(!array.verify_neighbour_counts());
+    }
+
+    #[test]
+    #[should_panic]
+    #[cfg(debug_assertions)]
+    fn test_debug_assert_neighbour_counts() {
+        let mut array = setup();
+        array[(2, 2)].spawn();
+        array.debug_assert_neighbour_counts();
+    }
+
+    // Roughly a third of the cells alive, from a xorshift so the core tests need no rand
+    fn random_array<const H: usize, const W: usize>(
+        seed: u64,
+        topology: Topology,
+    ) -> CellArray<H, W> {
+        let mut state = seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1;
+        let mut array = CellArray::with_topology(topology);
+        for y in 0..H as isize {
+            for x in 0..W as isize {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                if state % 3 == 1 {
+                    array.spawn(x, y);
+                }
+            }
+        }
+        array
+    }
+
+    #[test]
+    fn test_next_generation_into() {
+        let topologies = [Topology::Toroidal, Topology::Fixed, Topology::Reflective];
+        let rules = [
+            Rule::conway(),
+            Rule::new(&[3, 6], &[2, 3], 2),
+            Rule::brians_brain(),
+        ];
+
+        for seed in 0..8 {
+            for topology in topologies {
+                for rule in &rules {
+                    let mut array = random_array::<13, 17>(seed, topology);
+                    for _ in 0..5 {
+                        let mut expected = CellArray::with_topology(topology);
+                        array.step_into(&mut expected, rule, |_, _| {});
+                        let mut next = CellArray::with_topology(topology);
+                        array.next_generation_into(&mut next, rule);
+
+                        assert_eq!(next, expected, "seed {} {:?} {}", seed, topology, rule);
+                        assert_eq!(next.population(), expected.population());
+                        assert!(next.verify_neighbour_counts());
+                        // The running counts were kept, not rebuilt
+                        assert!(!next.counts_stale);
+                        array = next;
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_rows_iter() {
+        let mut array = CellArray::<3, 4>::new();
+        array.spawn(1, 0);
+        array.spawn(3, 2);
+
+        let rows: Vec<Vec<bool>> = array
+            .rows_iter()
+            .map(|row| row.iter().map(|cell| cell.alive()).collect())
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                vec![false, true, false, false],
+                vec![false, false, false, false],
+                vec![false, false, false, true],
+            ]
+        );
+        assert!(array.rows_iter().all(|row| row.len() == 4));
+    }
 }
@@ -0,0 +1,132 @@
+// A cell like Cell with the neighbour count in a byte of its own,
+// so it holds counts up to MAX_NEIGHBOURS instead of 8.
+// Meant for range 2 neighbourhoods with 24 neighbours, at twice the memory.
+// The state byte keeps Cell's alive bit and dying countdown:
+//  state [0, 1, 0, |0, 0, 0, 0, |0], neighbours 24 -> Cell with 2 generations of dying left
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct CellWide {
+    state: u8,
+    neighbours: u8,
+}
+
+impl CellWide {
+    // A range 2 Moore neighbourhood, the 5x5 square around the cell
+    pub const MAX_NEIGHBOURS: u8 = 24;
+
+    pub fn new() -> CellWide {
+        CellWide::default()
+    }
+
+    pub fn spawn(&mut self) {
+        self.state |= 1;
+    }
+
+    pub fn kill(&mut self) {
+        self.state &= !1;
+    }
+
+    pub fn toggle(&mut self) {
+        self.state ^= 1;
+    }
+
+    pub fn alive(&self) -> bool {
+        self.state & 1 == 1
+    }
+
+    pub fn neighbours(&self) -> u8 {
+        self.neighbours
+    }
+
+    pub fn set_neighbours(&mut self, count: u8) {
+        assert!(
+            count <= CellWide::MAX_NEIGHBOURS,
+            "Neighbor count must be between 0 and {}",
+            CellWide::MAX_NEIGHBOURS
+        );
+        self.neighbours = count;
+    }
+
+    // Dying countdown, 0 for alive and dead cells
+    pub fn dying(&self) -> u8 {
+        self.state >> 5
+    }
+
+    pub fn set_dying(&mut self, countdown: u8) {
+        assert!(countdown <= 7, "Dying countdown must be between 0 and 7");
+        self.state = (self.state & 0b0001_1111) | (countdown << 5);
+    }
+}
+
+#[cfg(test)]
+mod test_cell_wide {
+    use super::*;
+    use crate::gol::types::{Cell, CellArray, CellStorage, Grid, Rule};
+
+    #[test]
+    fn test_wide_count() {
+        let mut cell = CellWide::new();
+        cell.spawn();
+        cell.set_neighbours(24);
+        assert_eq!(cell.neighbours(), 24);
+        assert!(cell.alive());
+
+        // Counting goes past the 8 the packed Cell stops at
+        assert_eq!(<Cell as CellStorage>::MAX_NEIGHBOURS, 8);
+        let mut wide = CellWide::new();
+        for _ in 0..CellWide::MAX_NEIGHBOURS {
+            CellStorage::add_neighbour(&mut wide);
+        }
+        assert_eq!(wide.neighbours(), 24);
+
+        for _ in 0..CellWide::MAX_NEIGHBOURS {
+            CellStorage::remove_neighbour(&mut wide);
+        }
+        assert_eq!(wide.neighbours(), 0);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "neighbour count overflow")]
+    fn test_wide_overflow() {
+        let mut wide = CellWide::new();
+        wide.set_neighbours(CellWide::MAX_NEIGHBOURS);
+        CellStorage::add_neighbour(&mut wide);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_too_many_neighbours() {
+        CellWide::new().set_neighbours(CellWide::MAX_NEIGHBOURS + 1);
+    }
+
+    #[test]
+    fn test_dying() {
+        let mut cell = CellWide::new();
+        cell.set_neighbours(20);
+        cell.set_dying(2);
+        assert_eq!(cell.dying(), 2);
+        assert_eq!(cell.neighbours(), 20);
+        assert!(!cell.alive());
+        cell.set_dying(0);
+        let mut expected = CellWide::new();
+        expected.set_neighbours(20);
+        assert_eq!(cell, expected);
+    }
+
+    #[test]
+    fn test_blinker() {
+        // Boards of wide cells evolve like packed ones
+        let mut array = CellArray::<5, 5, CellWide>::new();
+        for x in 1..4 {
+            array.spawn(x, 2);
+        }
+        let mut next = CellArray::<5, 5, CellWide>::new();
+        array.step_into(&mut next, &Rule::conway(), |_, _| {});
+
+        assert_eq!(
+            next.live_coords().collect::<Vec<_>>(),
+            vec![(2, 1), (2, 2), (2, 3)]
+        );
+        assert!(next.verify_neighbour_counts());
+    }
+}
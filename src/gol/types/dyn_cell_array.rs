@@ -0,0 +1,196 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::gol::types::{Cell, CellStorage, Grid};
+
+// Heap allocated board whose dimensions are chosen at runtime.
+// Behaves like CellArray, the wrapping and neighbour bookkeeping come from Grid.
+// Cells are stored row by row, the cell at (x, y) is cells[y * width + x].
+#[derive(Debug, Clone)]
+pub struct DynCellArray<C = Cell> {
+    cells: Vec<C>,
+    width: usize,
+    height: usize,
+    alive_count: usize,
+    // Set once mut_cell handed out a cell, like CellArray's
+    counts_stale: bool,
+}
+
+impl<C: CellStorage> DynCellArray<C> {
+    pub fn new(width: usize, height: usize) -> Self {
+        assert!(width > 0 && height > 0, "board must not be empty");
+
+        DynCellArray {
+            cells: vec![C::empty(); width * height],
+            width,
+            height,
+            alive_count: 0,
+            counts_stale: false,
+        }
+    }
+
+    // Wrap a column index around the board
+    pub fn wrap_x(&self, x: isize) -> usize {
+        x.rem_euclid(self.width as isize) as usize
+    }
+
+    // Wrap a row index around the board
+    pub fn wrap_y(&self, y: isize) -> usize {
+        y.rem_euclid(self.height as isize) as usize
+    }
+
+    // Return a reference to the cell at (x, y)
+    pub fn cell(&self, x: isize, y: isize) -> &C {
+        &self.cells[self.wrap_y(y) * self.width + self.wrap_x(x)]
+    }
+
+    // Return a mutable reference to the cell at (x, y).
+    // The population stays right if the cell is spawned or killed through it.
+    pub fn mut_cell(&mut self, x: isize, y: isize) -> &mut C {
+        self.counts_stale = true;
+        self.cell_mut(x, y)
+    }
+
+    // mut_cell for the spawn and kill bookkeeping, which keeps the count itself
+    fn cell_mut(&mut self, x: isize, y: isize) -> &mut C {
+        let index = self.wrap_y(y) * self.width + self.wrap_x(x);
+        &mut self.cells[index]
+    }
+
+    pub fn rows(&self) -> usize {
+        self.height
+    }
+
+    pub fn cols(&self) -> usize {
+        self.width
+    }
+
+    // Kill every cell and clear all neighbour counts
+    pub fn reset(&mut self) {
+        self.cells.fill(C::empty());
+        self.alive_count = 0;
+        self.counts_stale = false;
+    }
+
+    // Number of alive cells on the board
+    pub fn population(&self) -> usize {
+        if self.counts_stale {
+            self.cells.iter().filter(|cell| cell.is_alive()).count()
+        } else {
+            self.alive_count
+        }
+    }
+
+    pub fn spawn(&mut self, x: isize, y: isize) {
+        Grid::spawn(self, x, y);
+    }
+
+    pub fn kill_cell(&mut self, x: isize, y: isize) {
+        Grid::kill_cell(self, x, y);
+    }
+
+    pub fn neighbour_coordinates(&self, x: isize, y: isize) -> [(isize, isize); 8] {
+        Grid::neighbour_coordinates(self, x, y)
+    }
+}
+
+impl<C: CellStorage> Grid for DynCellArray<C> {
+    type Cell = C;
+
+    fn rows(&self) -> usize {
+        self.height
+    }
+
+    fn cols(&self) -> usize {
+        self.width
+    }
+
+    fn cell(&self, x: isize, y: isize) -> &C {
+        DynCellArray::cell(self, x, y)
+    }
+
+    fn mut_cell(&mut self, x: isize, y: isize) -> &mut C {
+        self.cell_mut(x, y)
+    }
+
+    fn population(&self) -> usize {
+        DynCellArray::population(self)
+    }
+
+    fn record_alive(&mut self, _x: isize, _y: isize, alive: bool) {
+        // The cell already has its new state, so a recount includes it
+        if self.counts_stale {
+            self.alive_count = self.population();
+            self.counts_stale = false;
+        } else if alive {
+            self.alive_count += 1;
+        } else {
+            self.alive_count -= 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gol::types::CellArray;
+
+    fn glider_coords() -> [(isize, isize); 5] {
+        [(2, 0), (2, 1), (2, 2), (1, 2), (0, 1)]
+    }
+
+    #[test]
+    fn test_glider() {
+        let (width, height) = (5, 5);
+        let mut dyn_array = DynCellArray::<Cell>::new(width, height);
+        let mut cell_array = CellArray::<5, 5>::new();
+
+        for (x, y) in glider_coords() {
+            dyn_array.spawn(x, y);
+            cell_array.spawn(x, y);
+        }
+
+        assert_eq!(dyn_array.population(), 5);
+        assert_eq!(dyn_array.cell(0, 0).to_string(), "00000010");
+        assert_eq!(dyn_array.cell(0, 1).to_string(), "00000011");
+        assert_eq!(dyn_array.cell(1, 1).neighbours(), 5);
+        assert_eq!(dyn_array.cell(-1, 0).neighbours(), 1);
+
+        for y in 0..5 {
+            for x in 0..5 {
+                assert_eq!(dyn_array.cell(x, y).raw(), cell_array.cell(x, y).raw());
+            }
+        }
+
+        for (x, y) in glider_coords() {
+            dyn_array.kill_cell(x, y);
+        }
+        assert_eq!(dyn_array.population(), 0);
+        assert!((0..5).all(|y| (0..5).all(|x| dyn_array.cell(x, y).raw() == 0)));
+    }
+
+    #[test]
+    fn test_wrapping() {
+        let mut dyn_array = DynCellArray::<Cell>::new(7, 3);
+        dyn_array.spawn(0, 0);
+
+        assert!(dyn_array.cell(7, 3).alive());
+        assert!(dyn_array.cell(-7, -3).alive());
+        assert_eq!(dyn_array.cell(6, 2).neighbours(), 1);
+        assert_eq!(dyn_array.cell(6, 0).neighbours(), 1);
+        assert_eq!(dyn_array.cell(5, 0).neighbours(), 0);
+    }
+
+    #[test]
+    fn test_population_after_mut_cell() {
+        let mut dyn_array = DynCellArray::<Cell>::new(4, 4);
+        dyn_array.spawn(1, 1);
+        dyn_array.mut_cell(2, 2).spawn();
+        assert_eq!(dyn_array.population(), 2);
+        dyn_array.spawn(3, 3);
+        assert_eq!(dyn_array.population(), 3);
+        dyn_array.mut_cell(1, 1).kill();
+        dyn_array.kill_cell(3, 3);
+        assert_eq!(dyn_array.population(), 1);
+    }
+}
@@ -0,0 +1,138 @@
+use crate::gol::types::{CellStorage, Neighbourhood, Rule};
+
+// A toroidal board of cells. Implemented by the const sized CellArray and
+// the runtime sized DynCellArray, so both share the neighbour bookkeeping
+// of spawn and kill_cell and the Rule applied by step_into.
+pub trait Grid {
+    type Cell: CellStorage;
+
+    fn rows(&self) -> usize;
+
+    fn cols(&self) -> usize;
+
+    // Reference to the cell at (x, y), wrapping around the edges
+    fn cell(&self, x: isize, y: isize) -> &Self::Cell;
+
+    fn mut_cell(&mut self, x: isize, y: isize) -> &mut Self::Cell;
+
+    // False for coordinates that lie outside the board instead of wrapping
+    fn contains(&self, _x: isize, _y: isize) -> bool {
+        true
+    }
+
+    // Cells that spawn and kill_cell update the neighbour counts of
+    fn neighbourhood(&self) -> Neighbourhood {
+        Neighbourhood::Moore
+    }
+
+    fn population(&self) -> usize;
+
+    // Only called by spawn and kill_cell, after the cell at (x, y)
+    // changed state, to keep the alive counts up to date
+    fn record_alive(&mut self, x: isize, y: isize, alive: bool);
+
+    // Spawning an already alive cell is a no-op,
+    // otherwise the neighbour counts would be incremented twice.
+    // So is spawning outside the board.
+    fn spawn(&mut self, x: isize, y: isize) {
+        if !self.contains(x, y) || self.cell(x, y).is_alive() {
+            return;
+        }
+
+        self.mut_cell(x, y).set_alive(true);
+        self.record_alive(x, y, true);
+
+        for &(dx, dy) in self.neighbourhood().offsets() {
+            let (nx, ny) = (x.wrapping_add(dx), y.wrapping_add(dy));
+            if self.contains(nx, ny) {
+                self.mut_cell(nx, ny).add_neighbour();
+            }
+        }
+    }
+
+    // Killing an already dead cell is a no-op
+    fn kill_cell(&mut self, x: isize, y: isize) {
+        if !self.cell(x, y).is_alive() {
+            return;
+        }
+
+        self.mut_cell(x, y).set_alive(false);
+        self.record_alive(x, y, false);
+
+        for &(dx, dy) in self.neighbourhood().offsets() {
+            let (nx, ny) = (x.wrapping_add(dx), y.wrapping_add(dy));
+            if self.contains(nx, ny) {
+                self.mut_cell(nx, ny).remove_neighbour();
+            }
+        }
+    }
+
+    // The 8 Moore neighbours, regardless of the neighbourhood
+    fn neighbour_coordinates(&self, x: isize, y: isize) -> [(isize, isize); 8] {
+        [
+            (x.wrapping_sub(1), y.wrapping_sub(1)), // top_left
+            (x, y.wrapping_sub(1)),                 // top
+            (x.wrapping_add(1), y.wrapping_sub(1)), // top_right
+            (x.wrapping_sub(1), y),                 // left
+            (x.wrapping_add(1), y),                 // right
+            (x.wrapping_sub(1), y.wrapping_add(1)), // bottom_left
+            (x, y.wrapping_add(1)),                 // bottom
+            (x.wrapping_add(1), y.wrapping_add(1)), // bottom_right
+        ]
+    }
+
+    // Spawn the successor of this board under `rule` into `next`, which must be empty.
+    // `record(neighbours, born)` is called for every cell that is born or dies,
+    // with the neighbour count it had. A cell that starts dying counts as dead.
+    fn step_into(&self, next: &mut Self, rule: &Rule, record: impl FnMut(u8, bool)) {
+        self.step_rows_into(next, rule, |_| true, record);
+    }
+
+    // Same as step_into but only visits the rows for which `active` is true.
+    // Skipping a row is only correct if it has no alive or dying cells
+    // and no alive neighbours.
+    fn step_rows_into(
+        &self,
+        next: &mut Self,
+        rule: &Rule,
+        active: impl Fn(usize) -> bool,
+        mut record: impl FnMut(u8, bool),
+    ) {
+        for y in 0..self.rows() {
+            if !active(y) {
+                continue;
+            }
+            let y = y as isize;
+
+            for x in 0..self.cols() as isize {
+                let cell = self.cell(x, y);
+                let dying = cell.dying();
+
+                // Dying cells count down and can not be born
+                if dying > 0 {
+                    next.mut_cell(x, y).set_dying(dying - 1);
+                    continue;
+                }
+                if !cell.is_alive() && cell.neighbours() == 0 {
+                    continue;
+                }
+
+                let neighbour_count = cell.neighbours();
+                let alive_next = if cell.is_alive() {
+                    rule.survives(neighbour_count)
+                } else {
+                    rule.born(neighbour_count)
+                };
+
+                if alive_next {
+                    next.spawn(x, y);
+                } else if cell.is_alive() {
+                    next.mut_cell(x, y).set_dying(rule.dying_states());
+                }
+                if cell.is_alive() != alive_next {
+                    record(neighbour_count, alive_next);
+                }
+            }
+        }
+    }
+}
@@ -1,5 +1,19 @@
 mod cell;
 mod cell_array;
+mod cell_wide;
+mod dyn_cell_array;
+mod grid;
+mod neighbourhood;
+mod rule;
+mod storage;
+mod topology;
 
 pub use cell::*;
-pub use cell_array::*;
\ No newline at end of file
+pub use cell_array::*;
+pub use cell_wide::*;
+pub use dyn_cell_array::*;
+pub use grid::*;
+pub use neighbourhood::*;
+pub use rule::*;
+pub use storage::*;
+pub use topology::*;
\ No newline at end of file
@@ -0,0 +1,31 @@
+// Which cells around a cell count as its neighbours
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum Neighbourhood {
+    // The 8 surrounding cells
+    #[default]
+    Moore,
+    // Only the 4 orthogonally adjacent cells
+    VonNeumann,
+}
+
+const MOORE: [(isize, isize); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+const VON_NEUMANN: [(isize, isize); 4] = [(0, -1), (-1, 0), (1, 0), (0, 1)];
+
+impl Neighbourhood {
+    // (dx, dy) of every neighbour relative to the cell
+    pub fn offsets(self) -> &'static [(isize, isize)] {
+        match self {
+            Neighbourhood::Moore => &MOORE,
+            Neighbourhood::VonNeumann => &VON_NEUMANN,
+        }
+    }
+}
@@ -0,0 +1,193 @@
+use core::fmt;
+
+use crate::gol::types::CellWide;
+
+// Highest neighbour count a rule can refer to, as many as the widest CellStorage holds
+pub const MAX_RULE_NEIGHBOURS: u8 = CellWide::MAX_NEIGHBOURS;
+
+// Most states a rule can have: alive, dead and up to 7 dying states,
+// as many as the countdown in the high bits of a Cell can hold
+pub const MAX_STATES: u8 = 9;
+
+// A life-like rule in B/S notation, with an optional "Generations" decay.
+// `birth` and `survival` are bitmasks over the neighbour counts 0 to MAX_RULE_NEIGHBOURS:
+// a dead cell with n neighbours is born if bit n of `birth` is set,
+// an alive cell with n neighbours survives if bit n of `survival` is set.
+// With more than 2 `states`, an alive cell that does not survive counts down
+// through states - 2 dying states before it is dead. Dying cells are not
+// alive, so they neither count as neighbours nor can be born again.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Rule {
+    birth: u32,
+    survival: u32,
+    states: u8,
+}
+
+impl Rule {
+    pub fn new(birth: &[u8], survival: &[u8], states: u8) -> Rule {
+        assert!(
+            (2..=MAX_STATES).contains(&states),
+            "a rule needs between 2 and {} states",
+            MAX_STATES
+        );
+        Rule {
+            birth: mask(birth),
+            survival: mask(survival),
+            states,
+        }
+    }
+
+    // B3/S23
+    pub fn conway() -> Rule {
+        Rule::new(&[3], &[2, 3], 2)
+    }
+
+    // B36/S23: Conway's rule plus births on 6 neighbours, which gives a replicator
+    pub fn highlife() -> Rule {
+        Rule::new(&[3, 6], &[2, 3], 2)
+    }
+
+    // B2/S: nothing survives, yet most patterns explode
+    pub fn seeds() -> Rule {
+        Rule::new(&[2], &[], 2)
+    }
+
+    // B2/S/3: every alive cell dies, spending one generation dying
+    pub fn brians_brain() -> Rule {
+        Rule::new(&[2], &[], 3)
+    }
+
+    pub fn born(&self, neighbours: u8) -> bool {
+        neighbours <= MAX_RULE_NEIGHBOURS && self.birth & (1 << neighbours) != 0
+    }
+
+    pub fn survives(&self, neighbours: u8) -> bool {
+        neighbours <= MAX_RULE_NEIGHBOURS && self.survival & (1 << neighbours) != 0
+    }
+
+    pub fn states(&self) -> u8 {
+        self.states
+    }
+
+    // Generations a cell spends dying after it stops being alive
+    pub fn dying_states(&self) -> u8 {
+        self.states - 2
+    }
+
+    // The rules there are constructors for, in the order they are cycled through
+    pub fn presets() -> [Rule; 4] {
+        [
+            Rule::conway(),
+            Rule::highlife(),
+            Rule::seeds(),
+            Rule::brians_brain(),
+        ]
+    }
+
+    // The preset after this rule, wrapping around. Rules that are not
+    // a preset continue with the first one.
+    pub fn next_preset(&self) -> Rule {
+        let presets = Rule::presets();
+        let next = presets
+            .iter()
+            .position(|rule| rule == self)
+            .map_or(0, |i| (i + 1) % presets.len());
+        presets[next]
+    }
+}
+
+impl Default for Rule {
+    fn default() -> Self {
+        Rule::conway()
+    }
+}
+
+// B3/S23, or B2/S/3 with the number of states appended
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let digits = |f: &mut fmt::Formatter<'_>, mask: u32| -> fmt::Result {
+            (0..=MAX_RULE_NEIGHBOURS)
+                .filter(|n| mask & (1 << n) != 0)
+                .try_for_each(|n| write!(f, "{}", n))
+        };
+        write!(f, "B")?;
+        digits(f, self.birth)?;
+        write!(f, "/S")?;
+        digits(f, self.survival)?;
+        if self.states > 2 {
+            write!(f, "/{}", self.states)?;
+        }
+        Ok(())
+    }
+}
+
+fn mask(counts: &[u8]) -> u32 {
+    counts.iter().fold(0, |mask, &n| {
+        assert!(
+            n <= MAX_RULE_NEIGHBOURS,
+            "neighbour counts go up to {}",
+            MAX_RULE_NEIGHBOURS
+        );
+        mask | (1 << n)
+    })
+}
+
+#[cfg(test)]
+mod test_rule {
+    use super::*;
+
+    #[test]
+    fn test_conway() {
+        let rule = Rule::default();
+        assert_eq!(rule, Rule::conway());
+        assert_eq!(rule.states(), 2);
+        assert_eq!(rule.dying_states(), 0);
+        for n in 0..=8 {
+            assert_eq!(rule.born(n), n == 3, "born with {}", n);
+            assert_eq!(rule.survives(n), n == 2 || n == 3, "survives with {}", n);
+        }
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(Rule::conway().to_string(), "B3/S23");
+        assert_eq!(Rule::brians_brain().to_string(), "B2/S/3");
+        assert_eq!(Rule::seeds().to_string(), "B2/S");
+        assert_eq!(Rule::highlife().to_string(), "B36/S23");
+    }
+
+    #[test]
+    fn test_next_preset() {
+        let mut rule = Rule::conway();
+        let mut seen = Vec::new();
+        for _ in 0..Rule::presets().len() {
+            seen.push(rule);
+            rule = rule.next_preset();
+        }
+        assert_eq!(rule, Rule::conway());
+        assert_eq!(seen, Rule::presets());
+
+        assert_eq!(Rule::new(&[1], &[1], 2).next_preset(), Rule::conway());
+    }
+
+    #[test]
+    fn test_wide_counts() {
+        let rule = Rule::new(&[MAX_RULE_NEIGHBOURS], &[12], 2);
+        assert!(rule.born(MAX_RULE_NEIGHBOURS));
+        assert!(rule.survives(12));
+        assert!(!rule.born(MAX_RULE_NEIGHBOURS + 1));
+        assert!(!rule.survives(u8::MAX));
+    }
+
+    #[test]
+    #[should_panic(expected = "neighbour counts go up to")]
+    fn test_count_too_high() {
+        Rule::new(&[MAX_RULE_NEIGHBOURS + 1], &[], 2);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_too_many_states() {
+        Rule::new(&[2], &[], MAX_STATES + 1);
+    }
+}
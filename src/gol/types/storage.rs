@@ -0,0 +1,130 @@
+use crate::gol::types::{Cell, CellWide};
+
+// How the state of a single cell is packed.
+// CellArray and Engine only talk to cells through this trait,
+// so alternative layouts (wider counts, extra per-cell data, ...)
+// can be swapped in without touching the simulation logic.
+pub trait CellStorage: Copy {
+    // Largest neighbour count the layout can hold
+    const MAX_NEIGHBOURS: u8 = 8;
+
+    // A dead cell with no neighbours
+    fn empty() -> Self;
+
+    fn is_alive(&self) -> bool;
+
+    fn set_alive(&mut self, alive: bool);
+
+    fn neighbours(&self) -> u8;
+
+    fn set_neighbours(&mut self, count: u8);
+
+    // Generations a dying cell has left, see Rule.
+    // Layouts without room for it only support 2 state rules.
+    fn dying(&self) -> u8 {
+        0
+    }
+
+    fn set_dying(&mut self, _countdown: u8) {}
+
+    // Counts outside 0 to MAX_NEIGHBOURS are a bookkeeping bug,
+    // they panic in debug builds and saturate in release builds
+    fn add_neighbour(&mut self) {
+        debug_assert!(
+            self.neighbours() < Self::MAX_NEIGHBOURS,
+            "neighbour count overflow"
+        );
+        if self.neighbours() < Self::MAX_NEIGHBOURS {
+            self.set_neighbours(self.neighbours() + 1);
+        }
+    }
+
+    fn remove_neighbour(&mut self) {
+        debug_assert!(self.neighbours() > 0, "neighbour count underflow");
+        if self.neighbours() > 0 {
+            self.set_neighbours(self.neighbours() - 1);
+        }
+    }
+}
+
+// The default packing: alive bit and neighbour count share one byte
+impl CellStorage for Cell {
+    const MAX_NEIGHBOURS: u8 = Cell::MAX_NEIGHBOURS;
+
+    fn empty() -> Self {
+        Cell::new()
+    }
+
+    fn is_alive(&self) -> bool {
+        self.alive()
+    }
+
+    fn set_alive(&mut self, alive: bool) {
+        if alive {
+            self.spawn();
+        } else {
+            self.kill();
+        }
+    }
+
+    fn neighbours(&self) -> u8 {
+        Cell::neighbours(self)
+    }
+
+    fn set_neighbours(&mut self, count: u8) {
+        Cell::set_neighbours(self, count);
+    }
+
+    fn dying(&self) -> u8 {
+        Cell::dying(self)
+    }
+
+    fn set_dying(&mut self, countdown: u8) {
+        Cell::set_dying(self, countdown);
+    }
+
+    fn add_neighbour(&mut self) {
+        Cell::add_neighbour(self);
+    }
+
+    fn remove_neighbour(&mut self) {
+        Cell::remove_neighbour(self);
+    }
+}
+
+// Neighbour count in a byte of its own, for neighbourhoods beyond the 8 Moore cells
+impl CellStorage for CellWide {
+    const MAX_NEIGHBOURS: u8 = CellWide::MAX_NEIGHBOURS;
+
+    fn empty() -> Self {
+        CellWide::new()
+    }
+
+    fn is_alive(&self) -> bool {
+        self.alive()
+    }
+
+    fn set_alive(&mut self, alive: bool) {
+        if alive {
+            self.spawn();
+        } else {
+            self.kill();
+        }
+    }
+
+    fn neighbours(&self) -> u8 {
+        CellWide::neighbours(self)
+    }
+
+    fn set_neighbours(&mut self, count: u8) {
+        CellWide::set_neighbours(self, count);
+    }
+
+    fn dying(&self) -> u8 {
+        CellWide::dying(self)
+    }
+
+    fn set_dying(&mut self, countdown: u8) {
+        CellWide::set_dying(self, countdown);
+    }
+}
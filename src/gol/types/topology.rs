@@ -0,0 +1,115 @@
+// How coordinates outside the board are mapped back onto it
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum Topology {
+    // Leaving one edge enters from the opposite one
+    #[default]
+    Toroidal,
+    // Everything outside the board is dead
+    Fixed,
+    // The board is mirrored at its edges: -1 maps to 0, len maps to len - 1
+    Reflective,
+    // Toroidal, but crossing the bottom edge moves `shift_x` columns to the right
+    // and crossing the right edge moves `shift_y` rows down, the top and left edges
+    // shift the other way. Without shifts this is the same as Toroidal.
+    // At most one of the shifts may be set, with both the neighbours of a cell
+    // would not all count it as their neighbour (see CellArray::with_topology).
+    Twisted {
+        shift_x: isize,
+        shift_y: isize,
+    },
+}
+
+impl Topology {
+    // Map a coordinate on an axis of `len` cells onto the board,
+    // None if it lies outside a Fixed board. The shifts of a Twisted board
+    // depend on the other axis, so it wraps like a Toroidal one here.
+    pub fn resolve(self, i: isize, len: usize) -> Option<usize> {
+        let len = len as isize;
+        match self {
+            Topology::Toroidal | Topology::Twisted { .. } => Some(i.rem_euclid(len) as usize),
+            Topology::Fixed => (0..len).contains(&i).then_some(i as usize),
+            Topology::Reflective => {
+                let folded = i.rem_euclid(2 * len);
+                Some(if folded < len {
+                    folded
+                } else {
+                    2 * len - 1 - folded
+                } as usize)
+            }
+        }
+    }
+
+    // Map (x, y) onto a board of `width` x `height` cells,
+    // None if it lies outside a Fixed board
+    pub fn resolve_point(
+        self,
+        x: isize,
+        y: isize,
+        width: usize,
+        height: usize,
+    ) -> Option<(usize, usize)> {
+        let Topology::Twisted { shift_x, shift_y } = self else {
+            return Some((self.resolve(x, width)?, self.resolve(y, height)?));
+        };
+        let (w, h) = (width as isize, height as isize);
+
+        let x = x + y.div_euclid(h) * shift_x;
+        let y = y + x.div_euclid(w) * shift_y;
+        Some((x.rem_euclid(w) as usize, y.rem_euclid(h) as usize))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve() {
+        assert_eq!(Topology::Toroidal.resolve(-1, 5), Some(4));
+        assert_eq!(Topology::Toroidal.resolve(5, 5), Some(0));
+
+        assert_eq!(Topology::Fixed.resolve(-1, 5), None);
+        assert_eq!(Topology::Fixed.resolve(4, 5), Some(4));
+        assert_eq!(Topology::Fixed.resolve(5, 5), None);
+
+        assert_eq!(Topology::Reflective.resolve(-1, 5), Some(0));
+        assert_eq!(Topology::Reflective.resolve(-2, 5), Some(1));
+        assert_eq!(Topology::Reflective.resolve(2, 5), Some(2));
+        assert_eq!(Topology::Reflective.resolve(5, 5), Some(4));
+        assert_eq!(Topology::Reflective.resolve(6, 5), Some(3));
+    }
+
+    #[test]
+    fn test_resolve_point() {
+        let untwisted = Topology::Twisted {
+            shift_x: 0,
+            shift_y: 0,
+        };
+        for y in -7..12 {
+            for x in -9..14 {
+                assert_eq!(
+                    untwisted.resolve_point(x, y, 4, 5),
+                    Topology::Toroidal.resolve_point(x, y, 4, 5)
+                );
+            }
+        }
+
+        let twisted = Topology::Twisted {
+            shift_x: 2,
+            shift_y: 0,
+        };
+        assert_eq!(twisted.resolve_point(1, 1, 6, 4), Some((1, 1)));
+        assert_eq!(twisted.resolve_point(1, -1, 6, 4), Some((5, 3)));
+        assert_eq!(twisted.resolve_point(5, 4, 6, 4), Some((1, 0)));
+        assert_eq!(twisted.resolve_point(-1, 2, 6, 4), Some((5, 2)));
+
+        let twisted = Topology::Twisted {
+            shift_x: 0,
+            shift_y: -1,
+        };
+        assert_eq!(twisted.resolve_point(6, 0, 6, 4), Some((0, 3)));
+        assert_eq!(twisted.resolve_point(-1, 3, 6, 4), Some((5, 0)));
+
+        assert_eq!(Topology::Fixed.resolve_point(-1, 0, 6, 4), None);
+    }
+}
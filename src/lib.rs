@@ -0,0 +1,8 @@
+// Without the default `std` feature only the core simulation in gol::types
+// is built, on top of `core` and `alloc`. The engines, the display,
+// randomizing and file I/O all need `std`.
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
+
+pub mod gol;
@@ -1,21 +1,73 @@
-mod gol;
-use gol::*;
-use std::cell::RefCell;
+mod cli;
 
-const H: usize = 100;
-const W: usize = 100;
-const GENERATIONS: usize = 1000;
-const DELAY: usize = 20;
+use cli::{Options, Size};
+use gameoflife::gol::patterns::pattern_by_name;
+use gameoflife::gol::*;
+use std::cell::RefCell;
 
 fn main() {
+    let options = match cli::parse(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("{}\n\n{}", err, cli::USAGE);
+            std::process::exit(2);
+        }
+    };
+    if options.help {
+        println!("{}", cli::USAGE);
+        return;
+    }
+
+    match options.size {
+        Size::Small => run::<50, 50>(&options),
+        Size::Medium => run::<100, 100>(&options),
+        Size::Large => run::<200, 400>(&options),
+    }
+}
+
+fn run<const H: usize, const W: usize>(options: &Options) {
     let engine = RefCell::new(Engine::<H, W>::new());
-    let mut display = Display::<H, W>::new(&engine, DELAY);
-    engine.borrow_mut().randomize();
+    seed(&mut engine.borrow_mut(), options);
 
+    if options.headless {
+        let generations = options.generations.unwrap_or(cli::GENERATIONS);
+        run_headless(&engine, generations, |_, _| {});
 
-    for _ in 0..GENERATIONS {
-        engine.borrow_mut().generate();
-        display.update();
+        let engine = engine.borrow();
+        println!("generation: {}", engine.generation());
+        println!("population: {}", engine.population());
+        return;
     }
 
+    let mut display = match Display::<H, W>::new(&engine, options.delay) {
+        Ok(display) => display,
+        Err(err) => {
+            eprintln!("{}, use --headless to run without a window", err);
+            std::process::exit(1);
+        }
+    };
+    let result = match options.generations {
+        Some(generations) => (0..generations).try_for_each(|_| {
+            engine.borrow_mut().step();
+            display.update()
+        }),
+        None => display.run(),
+    };
+    match result {
+        Ok(()) | Err(DisplayError::Closed) => {}
+        Err(err) => eprintln!("{}", err),
+    }
+}
+
+// Start from the chosen pattern in the middle of the board, or a random board
+fn seed<const H: usize, const W: usize>(engine: &mut Engine<H, W>, options: &Options) {
+    match (&options.pattern, options.seed) {
+        (Some(name), _) => {
+            let pattern = pattern_by_name(name).unwrap();
+            engine.spawn_centered(pattern.cells());
+            engine.mark_start();
+        }
+        (None, Some(seed)) => engine.randomize_seeded(seed, 0.5),
+        (None, None) => engine.randomize(),
+    }
 }
@@ -0,0 +1,40 @@
+// The binary needs the std feature
+#![cfg(feature = "std")]
+
+use std::process::Command;
+
+fn run_headless(args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_gameoflife"))
+        .arg("--headless")
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn test_headless_pattern() {
+    // A blinker never changes its population
+    let output = run_headless(&["--pattern", "blinker", "--generations", "7"]);
+    assert_eq!(output, "generation: 7\npopulation: 3\n");
+}
+
+#[test]
+fn test_headless_seed_is_deterministic() {
+    // The same seed always grows the same 50x50 soup
+    let args = ["--size", "small", "--seed", "42", "--generations", "100"];
+    let first = run_headless(&args);
+    assert_eq!(first, "generation: 100\npopulation: 201\n");
+    assert_eq!(run_headless(&args), first);
+}
+
+#[test]
+fn test_invalid_argument() {
+    let output = Command::new(env!("CARGO_BIN_EXE_gameoflife"))
+        .arg("--size")
+        .arg("huge")
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2));
+}
@@ -0,0 +1,34 @@
+// Only uses gol::types, so it also runs with `cargo test --no-default-features`
+// to check that the core simulation builds without std
+
+use gameoflife::gol::types::{CellArray, Grid, Rule};
+
+fn live(array: &CellArray<5, 5>) -> Vec<(isize, isize)> {
+    array.live_coords().collect()
+}
+
+#[test]
+fn blinker_oscillates() {
+    let horizontal = vec![(1, 2), (2, 2), (3, 2)];
+    let vertical = vec![(2, 1), (2, 2), (2, 3)];
+
+    let mut board = CellArray::<5, 5>::new();
+    for &(x, y) in &horizontal {
+        board.spawn(x, y);
+    }
+
+    let rule = Rule::conway();
+    for generation in 1..=4 {
+        let mut next = CellArray::<5, 5>::new();
+        board.step_into(&mut next, &rule, |_, _| {});
+        board = next;
+
+        let expected = if generation % 2 == 1 {
+            &vertical
+        } else {
+            &horizontal
+        };
+        assert_eq!(&live(&board), expected);
+        assert!(board.verify_neighbour_counts());
+    }
+}